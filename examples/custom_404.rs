@@ -3,37 +3,37 @@ extern crate ferrum_router;
 
 // To run, $ cargo run --example custom_404
 // To use, go to http://localhost:3000/foobar to see the custom 404
+// Or, go to http://localhost:3000/api/foobar to see the custom API 404
 // Or, go to http://localhost:3000 for a standard 200 OK
 
-use ferrum::{AfterMiddleware, Chain, Ferrum, FerrumError, FerrumResult, mime, Request, Response, StatusCode};
-use ferrum_router::{Router, NoRoute};
-
-struct Custom404;
-
-impl AfterMiddleware for Custom404 {
-    fn catch(&self, _: &mut Request, error: FerrumError) -> FerrumResult<Response> {
-        println!("Hitting custom 404 middleware");
-
-        if error.error.is::<NoRoute>() {
-            Ok(Response::new()
-                .with_content("Custom 404 response", mime::TEXT_PLAIN)
-                .with_status(StatusCode::NotFound))
-        } else {
-            Err(error)
-        }
-    }
-}
+use ferrum::{Ferrum, FerrumResult, mime, Request, Response, StatusCode};
+use ferrum_router::{Router, RouteRequest, Unmatched};
 
 fn main() {
     let mut router = Router::new();
     router.get("/", handler, None);
+    router.fallback_for("/api/{*path}", api_not_found);
+    router.fallback(not_found);
 
-    let mut chain = Chain::new(router);
-    chain.link_after(Custom404);
-
-    Ferrum::new(chain).http("localhost:3000").unwrap();
+    Ferrum::new(router).http("localhost:3000").unwrap();
 }
 
 fn handler(_: &mut Request) -> FerrumResult<Response> {
     Ok(Response::new().with_content("Handling response", mime::TEXT_PLAIN))
 }
+
+fn not_found(request: &mut Request) -> FerrumResult<Response> {
+    println!("Hitting custom 404 handler");
+
+    let unmatched = request.extensions.get::<Unmatched>().unwrap();
+    Ok(Response::new()
+        .with_content(format!("Custom 404 response for {} {}", unmatched.method, unmatched.path), mime::TEXT_PLAIN)
+        .with_status(StatusCode::NotFound))
+}
+
+fn api_not_found(request: &mut Request) -> FerrumResult<Response> {
+    let content = format!("{{\"error\":\"No API endpoint /{}\"}}", request.param("path")?);
+    Ok(Response::new()
+        .with_content(content, mime::APPLICATION_JSON)
+        .with_status(StatusCode::NotFound))
+}
@@ -14,7 +14,7 @@ fn handler(_: &mut Request) -> FerrumResult<Response> {
 
 fn query_handler(request: &mut Request) -> FerrumResult<Response> {
     let params = request.extensions.get::<Router>().unwrap();
-    let query = params.get("query").map(|value| value.as_str()).unwrap_or("/");
+    let query = params.get("query").unwrap_or("/");
     Ok(Response::new().with_content(query, mime::TEXT_PLAIN))
 }
 
@@ -23,7 +23,7 @@ fn main() {
 
     fn query_handler(request: &mut Request) -> FerrumResult<Response> {
         let query = request.extensions.get::<Router>().unwrap()
-            .get("query").map(|value| value.as_str()).unwrap_or("/");
+            .get("query").unwrap_or("/");
         Ok(Response::new().with_content(query, mime::TEXT_PLAIN))
     }
 }
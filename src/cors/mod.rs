@@ -0,0 +1,331 @@
+use std::error::Error;
+use std::fmt;
+
+use ferrum::{Request, Response, FerrumResult, FerrumError};
+use ferrum::{header, Headers, Method, StatusCode};
+use regex::Regex;
+use unicase::Ascii;
+
+/// The set of origins a `Cors` policy accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Any origin is accepted.
+    Any,
+    /// Only the listed origins (e.g. `https://example.com`) are accepted.
+    List(Vec<String>),
+}
+
+/// The set of request headers a `Cors` policy accepts in a preflight request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedHeaders {
+    /// Any requested header is accepted and echoed back.
+    Any,
+    /// Only the listed headers are accepted.
+    List(Vec<Ascii<String>>),
+}
+
+/// Cross-Origin Resource Sharing policy.
+///
+/// A policy can be set router-wide with `Router::cors` or for the paths matching a glob with
+/// `Router::cors_for`. Preflight requests are answered by the router using the methods from
+/// the route table, actual requests get the `Access-Control-Allow-*` headers added to
+/// their responses, and requests from disallowed origins are rejected with a `CorsError`.
+///
+/// A request which `Origin` is the scheme and host of the request itself, like the same-origin
+/// form posts of browsers, isn't checked and gets no CORS headers. A preflight request for a
+/// path with an explicit `options` route is dispatched to the route, the policy only adds its
+/// headers to the response.
+///
+/// ```
+/// use ferrum_router::{Router, Cors};
+///
+/// let mut router = Router::new();
+/// router.cors(Cors::new()
+///     .with_origin("https://example.com")
+///     .with_allowed_header("content-type")
+///     .with_max_age(3600));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cors {
+    pub origins: AllowedOrigins,
+    pub allowed_headers: AllowedHeaders,
+    pub exposed_headers: Vec<Ascii<String>>,
+    pub credentials: bool,
+    pub max_age: Option<u32>,
+}
+
+impl Cors {
+    /// Construct a policy that accepts no origins and no extra request headers.
+    pub fn new() -> Cors {
+        Cors {
+            origins: AllowedOrigins::List(Vec::new()),
+            allowed_headers: AllowedHeaders::List(Vec::new()),
+            exposed_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Construct a permissive policy that accepts any origin and any request headers.
+    pub fn permissive() -> Cors {
+        Cors::new()
+            .with_any_origin()
+            .with_any_header()
+    }
+
+    /// Accept the given origin, e.g. `https://example.com`.
+    pub fn with_origin<O: Into<String>>(mut self, origin: O) -> Self {
+        let origin = origin.into().to_lowercase();
+        match self.origins {
+            AllowedOrigins::List(ref mut origins) => origins.push(origin),
+            AllowedOrigins::Any => self.origins = AllowedOrigins::List(vec![origin]),
+        }
+        self
+    }
+
+    /// Accept any origin.
+    pub fn with_any_origin(mut self) -> Self {
+        self.origins = AllowedOrigins::Any;
+        self
+    }
+
+    /// Accept the given request header in preflight requests.
+    pub fn with_allowed_header<H: Into<String>>(mut self, name: H) -> Self {
+        let name = Ascii::new(name.into());
+        match self.allowed_headers {
+            AllowedHeaders::List(ref mut names) => names.push(name),
+            AllowedHeaders::Any => self.allowed_headers = AllowedHeaders::List(vec![name]),
+        }
+        self
+    }
+
+    /// Accept any request headers in preflight requests.
+    pub fn with_any_header(mut self) -> Self {
+        self.allowed_headers = AllowedHeaders::Any;
+        self
+    }
+
+    /// Expose the given response header to the client.
+    pub fn with_exposed_header<H: Into<String>>(mut self, name: H) -> Self {
+        self.exposed_headers.push(Ascii::new(name.into()));
+        self
+    }
+
+    /// Allow requests with credentials (cookies, authorization headers).
+    pub fn with_credentials(mut self, credentials: bool) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Let clients cache preflight results for `seconds`.
+    pub fn with_max_age(mut self, seconds: u32) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Check whether `origin` is accepted by this policy.
+    pub fn is_origin_allowed(&self, origin: &str) -> bool {
+        match self.origins {
+            AllowedOrigins::Any => true,
+            AllowedOrigins::List(ref origins) => {
+                origins.contains(&origin.to_lowercase())
+            }
+        }
+    }
+
+    /// Check whether all of the request `names` are accepted by this policy.
+    pub fn are_headers_allowed(&self, names: &[Ascii<String>]) -> bool {
+        match self.allowed_headers {
+            AllowedHeaders::Any => true,
+            AllowedHeaders::List(ref allowed) => names.iter().all(|name| allowed.contains(name)),
+        }
+    }
+
+    /// Answer a preflight request for a path dispatchable with the `allowed` methods.
+    ///
+    /// A request without an origin or a requested method isn't a preflight request, see
+    /// `is_preflight`, and results in a `CorsError::NotPreflight` error.
+    pub fn preflight(&self, request: &Request, allowed: &[Method]) -> FerrumResult<Response> {
+        if !is_preflight(request) {
+            return Err(CorsError::NotPreflight.into());
+        }
+        let origin = self.check_origin(request)?
+            .ok_or(CorsError::NotPreflight)?;
+
+        let requested_method = request.headers.get::<header::AccessControlRequestMethod>()
+            .map(|method| method.0.clone())
+            .ok_or(CorsError::NotPreflight)?;
+        if !allowed.contains(&requested_method) {
+            return Err(CorsError::MethodNotAllowed(requested_method).into());
+        }
+
+        let requested_headers = request.headers.get::<header::AccessControlRequestHeaders>()
+            .map(|headers| headers.0.clone())
+            .unwrap_or_default();
+        if !self.are_headers_allowed(&requested_headers) {
+            return Err(CorsError::HeadersNotAllowed(requested_headers).into());
+        }
+
+        let mut response = Response::new().with_status(StatusCode::Ok);
+        self.apply(&origin, &mut response.headers);
+
+        response.headers.set(header::AccessControlAllowMethods(allowed.to_vec()));
+        if !requested_headers.is_empty() {
+            let allowed_headers = match self.allowed_headers {
+                AllowedHeaders::Any => requested_headers,
+                AllowedHeaders::List(ref names) => names.clone(),
+            };
+            response.headers.set(header::AccessControlAllowHeaders(allowed_headers));
+        }
+        if let Some(max_age) = self.max_age {
+            response.headers.set(header::AccessControlMaxAge(max_age));
+        }
+        Ok(response)
+    }
+
+    /// Return the origin of a cross-origin request, `None` for same-origin requests,
+    /// or an error if the origin isn't accepted.
+    pub fn check_origin(&self, request: &Request) -> Result<Option<String>, CorsError> {
+        match request_origin(&request.headers) {
+            Some(ref origin) if is_same_origin(request, origin) => Ok(None),
+            Some(origin) => if self.is_origin_allowed(&origin) {
+                Ok(Some(origin))
+            } else {
+                Err(CorsError::OriginNotAllowed(origin))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Set the `Access-Control-Allow-*` headers of a response to a request from `origin`.
+    pub fn apply(&self, origin: &str, headers: &mut Headers) {
+        if self.origins == AllowedOrigins::Any && !self.credentials {
+            headers.set(header::AccessControlAllowOrigin::Any);
+        } else {
+            headers.set(header::AccessControlAllowOrigin::Value(origin.to_string()));
+            vary_on_origin(headers);
+        }
+        if self.credentials {
+            headers.set(header::AccessControlAllowCredentials);
+        }
+        if !self.exposed_headers.is_empty() {
+            headers.set(header::AccessControlExposeHeaders(self.exposed_headers.clone()));
+        }
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Cors::new()
+    }
+}
+
+/// A `Cors` policy applied to the paths matching a glob.
+pub struct CorsScope {
+    pub glob_regex: Regex,
+    pub policy: Cors,
+}
+
+/// Check whether a request is a CORS preflight request.
+pub fn is_preflight(request: &Request) -> bool {
+    request.method == Method::Options
+        && request_origin(&request.headers).is_some()
+        && request.headers.get::<header::AccessControlRequestMethod>().is_some()
+}
+
+fn request_origin(headers: &Headers) -> Option<String> {
+    headers.get_raw("Origin")
+        .and_then(|raw| raw.one())
+        .map(|origin| String::from_utf8_lossy(origin).into_owned())
+}
+
+/// Check whether `origin` is the scheme and host of the request, as given by its URI or else
+/// its `Host` header. The scheme is only compared if the URI has one.
+fn is_same_origin(request: &Request, origin: &str) -> bool {
+    let (scheme, host) = match origin.find("://") {
+        Some(index) => (&origin[..index], &origin[index + 3..]),
+        None => return false,
+    };
+    if let Some(own_scheme) = request.uri.scheme() {
+        if !own_scheme.eq_ignore_ascii_case(scheme) {
+            return false;
+        }
+    }
+    let own_host = match request.uri.authority() {
+        Some(authority) => authority.to_string(),
+        None => match request.headers.get_raw("Host").and_then(|raw| raw.one()) {
+            Some(host) => String::from_utf8_lossy(host).into_owned(),
+            None => return false,
+        },
+    };
+    without_default_port(scheme, host).eq_ignore_ascii_case(without_default_port(scheme, &own_host))
+}
+
+fn without_default_port<'a>(scheme: &str, host: &'a str) -> &'a str {
+    let default_port = match &*scheme.to_lowercase() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => return host,
+    };
+    host.strip_suffix(default_port).unwrap_or(host)
+}
+
+fn vary_on_origin(headers: &mut Headers) {
+    let origin = Ascii::new("Origin".to_string());
+    let vary = match headers.get::<header::Vary>() {
+        Some(header::Vary::Any) => return,
+        Some(header::Vary::Items(items)) if items.contains(&origin) => return,
+        Some(header::Vary::Items(items)) => {
+            let mut items = items.clone();
+            items.push(origin);
+            items
+        },
+        None => vec![origin],
+    };
+    headers.set(header::Vary::Items(vary));
+}
+
+/// The error thrown by router if a cross-origin request is rejected by the `Cors` policy,
+/// it is always accompanied by a Forbidden response, or a BadRequest one for `NotPreflight`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CorsError {
+    OriginNotAllowed(String),
+    MethodNotAllowed(Method),
+    HeadersNotAllowed(Vec<Ascii<String>>),
+    /// `Cors::preflight` was called with a request which isn't a preflight request.
+    NotPreflight,
+}
+
+impl fmt::Display for CorsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CorsError::OriginNotAllowed(ref origin) =>
+                write!(f, "Origin `{}` is not allowed.", origin),
+            CorsError::MethodNotAllowed(ref method) =>
+                write!(f, "Method `{}` is not allowed.", method),
+            CorsError::HeadersNotAllowed(ref headers) => {
+                let headers = headers.iter().map(|name| name.as_ref()).collect::<Vec<&str>>();
+                write!(f, "Headers `{}` are not allowed.", headers.join(", "))
+            },
+            CorsError::NotPreflight =>
+                f.write_str("The request isn't a CORS preflight request."),
+        }
+    }
+}
+
+impl Error for CorsError {
+    fn description(&self) -> &str { "CORS request rejected" }
+}
+
+impl From<CorsError> for FerrumError {
+    fn from(error: CorsError) -> FerrumError {
+        let status = match error {
+            CorsError::NotPreflight => StatusCode::BadRequest,
+            _ => StatusCode::Forbidden,
+        };
+        FerrumError::new(error, Some(Response::new().with_status(status)))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,259 @@
+use super::*;
+
+use ferrum::{Handler, Request, Response, mime};
+use ferrum::request::HyperRequest;
+use router::Router;
+
+fn cors_request(method: Method, path: &str, origin: Option<&str>) -> Request {
+    let uri = format!("http://localhost{}", path).parse().unwrap();
+    let mut request = Request::new(HyperRequest::new(method, uri));
+    if let Some(origin) = origin {
+        request.headers.set_raw("Origin", origin.to_string());
+    }
+    request
+}
+
+fn preflight(path: &str, origin: &str, method: Method, headers: &[&str]) -> Request {
+    let mut request = cors_request(Method::Options, path, Some(origin));
+    request.headers.set(header::AccessControlRequestMethod(method));
+    if !headers.is_empty() {
+        request.headers.set(header::AccessControlRequestHeaders(
+            headers.iter().map(|name| Ascii::new(name.to_string())).collect()
+        ));
+    }
+    request
+}
+
+fn router_with_routes() -> Router {
+    let mut router = Router::new();
+    router.get("/users", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.post("/users", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.get("/public", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router
+}
+
+#[test]
+fn test_origin_allowed() {
+    let policy = Cors::new()
+        .with_origin("https://Example.com")
+        .with_origin("http://localhost:8080");
+
+    assert!(policy.is_origin_allowed("https://example.com"));
+    assert!(policy.is_origin_allowed("http://localhost:8080"));
+    assert!(!policy.is_origin_allowed("http://example.com"));
+    assert!(!policy.is_origin_allowed("null"));
+
+    assert!(Cors::permissive().is_origin_allowed("https://any.org"));
+    assert!(!Cors::new().is_origin_allowed("https://any.org"));
+}
+
+#[test]
+fn test_headers_allowed() {
+    let policy = Cors::new().with_allowed_header("Content-Type");
+
+    assert!(policy.are_headers_allowed(&[]));
+    assert!(policy.are_headers_allowed(&[Ascii::new("content-type".to_string())]));
+    assert!(!policy.are_headers_allowed(&[
+        Ascii::new("content-type".to_string()),
+        Ascii::new("x-token".to_string()),
+    ]));
+    assert!(Cors::permissive().are_headers_allowed(&[Ascii::new("x-token".to_string())]));
+}
+
+#[test]
+fn test_preflight_from_route_table() {
+    let mut router = router_with_routes();
+    router.cors(Cors::new()
+        .with_origin("https://example.com")
+        .with_allowed_header("content-type")
+        .with_max_age(600));
+
+    let mut request = preflight("/users", "https://example.com", Method::Post, &["Content-Type"]);
+    let response = router.handle(&mut request).unwrap();
+
+    assert_eq!(StatusCode::Ok, response.status);
+    assert_eq!(
+        Some(&header::AccessControlAllowOrigin::Value("https://example.com".to_string())),
+        response.headers.get()
+    );
+    assert_eq!(
+        Some(&header::AccessControlAllowMethods(vec![Method::Get, Method::Post, Method::Head, Method::Options])),
+        response.headers.get()
+    );
+    assert_eq!(
+        Some(&header::AccessControlAllowHeaders(vec![Ascii::new("content-type".to_string())])),
+        response.headers.get()
+    );
+    assert_eq!(Some(&header::AccessControlMaxAge(600)), response.headers.get());
+    assert_eq!(
+        Some(&header::Vary::Items(vec![Ascii::new("Origin".to_string())])),
+        response.headers.get()
+    );
+}
+
+#[test]
+fn test_preflight_rejected() {
+    let mut router = router_with_routes();
+    router.cors(Cors::new().with_origin("https://example.com"));
+
+    let mut request = preflight("/users", "https://evil.com", Method::Post, &[]);
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!(
+        Some(&CorsError::OriginNotAllowed("https://evil.com".to_string())),
+        error.error.downcast_ref::<CorsError>()
+    );
+    assert_eq!(StatusCode::Forbidden, error.response.unwrap().status);
+
+    let mut request = preflight("/users", "https://example.com", Method::Delete, &[]);
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!(
+        Some(&CorsError::MethodNotAllowed(Method::Delete)),
+        error.error.downcast_ref::<CorsError>()
+    );
+
+    let mut request = preflight("/users", "https://example.com", Method::Post, &["x-token"]);
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!(
+        Some(&CorsError::HeadersNotAllowed(vec![Ascii::new("x-token".to_string())])),
+        error.error.downcast_ref::<CorsError>()
+    );
+}
+
+#[test]
+fn test_preflight_not_preflight() {
+    let policy = Cors::permissive();
+    let allowed = [Method::Get];
+
+    for request in &[
+        cors_request(Method::Options, "/users", None),
+        cors_request(Method::Options, "/users", Some("https://example.com")),
+        cors_request(Method::Get, "/users", Some("https://example.com")),
+    ] {
+        let error = policy.preflight(request, &allowed).unwrap_err();
+        assert_eq!(Some(&CorsError::NotPreflight), error.error.downcast_ref::<CorsError>());
+        assert_eq!(StatusCode::BadRequest, error.response.unwrap().status);
+    }
+}
+
+#[test]
+fn test_actual_request() {
+    let mut router = router_with_routes();
+    router.cors(Cors::new()
+        .with_origin("https://example.com")
+        .with_exposed_header("x-total-count")
+        .with_credentials(true));
+
+    let mut request = cors_request(Method::Get, "/users", Some("https://example.com"));
+    let response = router.handle(&mut request).unwrap();
+    assert_eq!(
+        Some(&header::AccessControlAllowOrigin::Value("https://example.com".to_string())),
+        response.headers.get()
+    );
+    assert!(response.headers.has::<header::AccessControlAllowCredentials>());
+    assert_eq!(
+        Some(&header::AccessControlExposeHeaders(vec![Ascii::new("x-total-count".to_string())])),
+        response.headers.get()
+    );
+
+    let mut request = cors_request(Method::Get, "/users", Some("https://evil.com"));
+    let error = router.handle(&mut request).unwrap_err();
+    assert!(error.error.is::<CorsError>());
+
+    let mut request = cors_request(Method::Get, "/users", None);
+    let response = router.handle(&mut request).unwrap();
+    assert!(!response.headers.has::<header::AccessControlAllowOrigin>());
+
+    let mut request = cors_request(Method::Get, "/unknown", Some("https://example.com"));
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!(
+        Some(&header::AccessControlAllowOrigin::Value("https://example.com".to_string())),
+        error.response.unwrap().headers.get()
+    );
+}
+
+#[test]
+fn test_same_origin_request() {
+    let mut router = router_with_routes();
+    router.cors(Cors::new().with_origin("https://example.com"));
+
+    for origin in &["http://localhost", "http://LOCALHOST:80"] {
+        let mut request = cors_request(Method::Post, "/users", Some(origin));
+        let response = router.handle(&mut request).unwrap();
+        assert!(!response.headers.has::<header::AccessControlAllowOrigin>());
+    }
+
+    let mut request = cors_request(Method::Post, "/users", Some("https://localhost"));
+    assert!(router.handle(&mut request).is_err());
+    let mut request = cors_request(Method::Post, "/users", Some("http://localhost:8080"));
+    assert!(router.handle(&mut request).is_err());
+
+    let mut request = Request::new(HyperRequest::new(Method::Post, "/users".parse().unwrap()));
+    request.headers.set_raw("Host", "example.org");
+    request.headers.set_raw("Origin", "https://example.org");
+    assert!(router.handle(&mut request).is_ok());
+    request.headers.set_raw("Host", "other.org");
+    assert!(router.handle(&mut request).is_err());
+}
+
+#[test]
+fn test_preflight_explicit_options_route() {
+    let mut router = router_with_routes();
+    router.options("/users", |_: &mut Request| {
+        Ok(Response::new().with_status(StatusCode::NoContent))
+    }, None);
+    router.cors(Cors::new().with_origin("https://example.com"));
+
+    let mut request = preflight("/users", "https://example.com", Method::Post, &[]);
+    let response = router.handle(&mut request).unwrap();
+    assert_eq!(StatusCode::NoContent, response.status);
+    assert_eq!(
+        Some(&header::AccessControlAllowOrigin::Value("https://example.com".to_string())),
+        response.headers.get()
+    );
+    assert!(!response.headers.has::<header::AccessControlAllowMethods>());
+
+    let mut request = preflight("/public", "https://example.com", Method::Get, &[]);
+    let response = router.handle(&mut request).unwrap();
+    assert!(response.headers.has::<header::AccessControlAllowMethods>());
+}
+
+#[test]
+fn test_scoped_policies() {
+    let mut router = router_with_routes();
+    router.cors_for("/public", Cors::permissive());
+    router.cors(Cors::new().with_origin("https://example.com"));
+
+    let mut request = cors_request(Method::Get, "/public", Some("https://any.org"));
+    let response = router.handle(&mut request).unwrap();
+    assert_eq!(Some(&header::AccessControlAllowOrigin::Any), response.headers.get());
+
+    let mut request = cors_request(Method::Get, "/users", Some("https://any.org"));
+    assert!(router.handle(&mut request).is_err());
+}
+
+#[test]
+fn test_no_policy() {
+    let router = router_with_routes();
+
+    let mut request = cors_request(Method::Get, "/users", Some("https://any.org"));
+    let response = router.handle(&mut request).unwrap();
+    assert!(!response.headers.has::<header::AccessControlAllowOrigin>());
+
+    let mut request = preflight("/users", "https://any.org", Method::Post, &[]);
+    let response = router.handle(&mut request).unwrap();
+    assert!(!response.headers.has::<header::AccessControlAllowOrigin>());
+    assert!(response.headers.has::<header::Allow>());
+
+    let mut router = router_with_routes();
+    router.cors_for("/public", Cors::permissive());
+
+    let mut request = cors_request(Method::Get, "/users", Some("https://any.org"));
+    let response = router.handle(&mut request).unwrap();
+    assert!(!response.headers.has::<header::AccessControlAllowOrigin>());
+}
@@ -0,0 +1,316 @@
+//! Deserialization of the route parameters into structs, available with the `serde` feature.
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct UserPath {
+//!     id: u64,
+//!     tab: Option<Tab>,
+//! }
+//!
+//! fn handler(request: &mut Request) -> FerrumResult<Response> {
+//!     let path: UserPath = request.route_params()?;
+//!     ...
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use ferrum::{Request, Response, FerrumError, StatusCode, mime};
+use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::de::value::BorrowedStrDeserializer;
+
+use recognizer::Params;
+use request::RouteRequest;
+
+/// Deserialize the route parameters into `T`.
+pub fn from_params<'de, T>(params: &'de Params) -> Result<T, DeserializeError>
+    where T: Deserialize<'de>
+{
+    T::deserialize(ParamsDeserializer::new(params))
+}
+
+/// Deserialization of the route parameters of a request.
+pub trait RouteParams {
+    /// Deserialize the parameters of the matched route into `T`.
+    ///
+    /// A parameter that can't be deserialized results in a `FerrumError` with a BadRequest
+    /// response naming it, a request not dispatched by a router in a `RouteError`.
+    fn route_params<T>(&self) -> Result<T, FerrumError>
+        where T: DeserializeOwned;
+}
+
+impl RouteParams for Request {
+    fn route_params<T>(&self) -> Result<T, FerrumError>
+        where T: DeserializeOwned
+    {
+        from_params(self.params()?).map_err(FerrumError::from)
+    }
+}
+
+/// A `Deserializer` over the route parameters, which are exposed as a map of their
+/// percent-decoded values in the glob order.
+pub struct ParamsDeserializer<'de> {
+    params: &'de Params,
+}
+
+impl<'de> ParamsDeserializer<'de> {
+    pub fn new(params: &'de Params) -> Self {
+        ParamsDeserializer { params }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ParamsDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_map(ParamsMapAccess {
+            params: self.params,
+            index: 0,
+            value: None,
+        })
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// The params in order with their percent-decoded values.
+struct ParamsMapAccess<'de> {
+    params: &'de Params,
+    index: usize,
+    value: Option<(&'de str, &'de str)>,
+}
+
+impl<'de> de::MapAccess<'de> for ParamsMapAccess<'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+        where K: DeserializeSeed<'de>
+    {
+        match self.params.get_index(self.index) {
+            Some(param) => {
+                let name = param.name;
+                self.index += 1;
+                self.value = Some((name, param.decoded));
+                seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some)
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+        where V: DeserializeSeed<'de>
+    {
+        let (name, value) = self.value.take()
+            .expect("`next_value_seed` is called after `next_key_seed`");
+        seed.deserialize(ValueDeserializer { value })
+            .map_err(|error| error.with_field(name))
+    }
+}
+
+/// A `Deserializer` of a single route parameter value.
+///
+/// Numbers and bools are parsed from the value, enums are matched by unit variant names and
+/// sequences are read from comma-separated values.
+pub struct ValueDeserializer<'de> {
+    value: &'de str,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    pub fn new(value: &'de str) -> Self {
+        ValueDeserializer { value }
+    }
+
+    fn parse<T>(&self) -> Result<T, DeserializeError>
+        where T: FromStr,
+              T::Err: fmt::Display
+    {
+        T::from_str(self.value)
+            .map_err(|error| de::Error::custom(format_args!("`{}`: {}", self.value, error)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+                where V: Visitor<'de>
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if self.value.is_empty() {
+            visitor.visit_unit()
+        } else {
+            Err(de::Error::invalid_value(de::Unexpected::Str(self.value), &"an empty value"))
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        let values = if self.value.is_empty() {
+            Vec::new()
+        } else {
+            self.value.split(',').map(ValueDeserializer::new).collect()
+        };
+        visitor.visit_seq(de::value::SeqDeserializer::new(values.into_iter()))
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.value))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeserializeError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// The error of the route parameters deserialization,
+/// it is always accompanied by a BadRequest response when converted into a `FerrumError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializeError {
+    /// The name of the failing parameter, if known.
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl DeserializeError {
+    fn with_field(mut self, field: &str) -> Self {
+        if self.field.is_none() {
+            self.field = Some(field.to_string());
+        }
+        self
+    }
+}
+
+impl de::Error for DeserializeError {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        DeserializeError {
+            field: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeserializeError {
+            field: Some(field.to_string()),
+            message: "missing".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.field {
+            Some(ref field) => write!(f, "Invalid route param `{}`: {}.", field, self.message),
+            None => write!(f, "Invalid route params: {}.", self.message),
+        }
+    }
+}
+
+impl Error for DeserializeError {
+    fn description(&self) -> &str { "Bad route params" }
+}
+
+impl From<DeserializeError> for FerrumError {
+    fn from(error: DeserializeError) -> FerrumError {
+        let response = Response::new()
+            .with_content(error.to_string(), mime::TEXT_PLAIN)
+            .with_status(StatusCode::BadRequest);
+        FerrumError::new(error, Some(response))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,127 @@
+use super::*;
+
+use ferrum::{Handler, Method, Request, Response, FerrumResult};
+use ferrum::request::HyperRequest;
+use router::Router;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Tab {
+    Posts,
+    Friends,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct UserPath {
+    id: u64,
+    name: String,
+    admin: bool,
+    score: f32,
+    tab: Option<Tab>,
+    tags: Vec<String>,
+    page: Option<u32>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct UserId(u64);
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Newtype {
+    id: UserId,
+}
+
+fn params(pairs: &[(&str, &str)]) -> Params {
+    pairs.iter()
+        .map(|&(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn deserialize_struct() {
+    let params = params(&[
+        ("id", "42"),
+        ("name", "bob%20smith"),
+        ("admin", "true"),
+        ("score", "0.5"),
+        ("tab", "friends"),
+        ("tags", "a,b,c"),
+        ("extra", "ignored"),
+    ]);
+
+    let path: UserPath = from_params(&params).unwrap();
+    assert_eq!(UserPath {
+        id: 42,
+        name: "bob smith".to_string(),
+        admin: true,
+        score: 0.5,
+        tab: Some(Tab::Friends),
+        tags: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        page: None,
+    }, path);
+
+    let path: Newtype = from_params(&params).unwrap();
+    assert_eq!(Newtype { id: UserId(42) }, path);
+
+    let map: ::std::collections::HashMap<String, String> = from_params(&params).unwrap();
+    assert_eq!(7, map.len());
+    assert_eq!("bob smith", map["name"]);
+}
+
+#[test]
+fn deserialize_errors() {
+    let mut params = params(&[
+        ("id", "42"),
+        ("name", "bob"),
+        ("admin", "true"),
+        ("score", "0.5"),
+        ("tags", ""),
+    ]);
+
+    let path: UserPath = from_params(&params).unwrap();
+    assert_eq!(Vec::<String>::new(), path.tags);
+    assert_eq!(None, path.tab);
+
+    params.insert("id".to_string(), "-1".to_string());
+    let error = from_params::<UserPath>(&params).unwrap_err();
+    assert_eq!(Some("id".to_string()), error.field);
+    assert_eq!("Invalid route param `id`: `-1`: invalid digit found in string.", error.to_string());
+
+    params.insert("id".to_string(), "1".to_string());
+    params.insert("admin".to_string(), "yes".to_string());
+    let error = from_params::<UserPath>(&params).unwrap_err();
+    assert_eq!(Some("admin".to_string()), error.field);
+
+    params.insert("admin".to_string(), "false".to_string());
+    params.insert("tab".to_string(), "photos".to_string());
+    let error = from_params::<UserPath>(&params).unwrap_err();
+    assert_eq!(Some("tab".to_string()), error.field);
+
+    params.remove("tab");
+    params.remove("name");
+    let error = from_params::<UserPath>(&params).unwrap_err();
+    assert_eq!(Some("name".to_string()), error.field);
+    assert_eq!("Invalid route param `name`: missing.", error.to_string());
+}
+
+#[test]
+fn route_params() {
+    fn handler(request: &mut Request) -> FerrumResult<Response> {
+        let path: Newtype = request.route_params()?;
+        Ok(Response::new().with_content(path.id.0.to_string(), mime::TEXT_PLAIN))
+    }
+
+    let mut router = Router::new();
+    router.get("/users/{id}", handler, None);
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/users/12".parse().unwrap())
+    );
+    assert!(router.handle(&mut request).is_ok());
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/users/bob".parse().unwrap())
+    );
+    let error = router.handle(&mut request).unwrap_err();
+    assert!(error.error.is::<DeserializeError>());
+    assert_eq!(StatusCode::BadRequest, error.response.unwrap().status);
+}
@@ -7,12 +7,30 @@
 extern crate ferrum;
 extern crate url;
 extern crate regex;
+extern crate unicase;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+#[macro_use]
+extern crate serde_derive;
 
-pub use router::{Router, NoRoute, Id};
-pub use recognizer::{Recognize, Recognizer, Params};
-pub use uri_for::{UriFor, uri_for};
+pub use router::{Router, NoRoute, Pass, Id, MatchedRoute, RouteObserver, RouteMiss, Unmatched};
+pub use router::{Problem, ErrorRenderer, ProblemJson, ProblemHtml};
+pub use recognizer::{Recognize, Recognizer, Params, ParseParam, ParamError, TypedParams, Segments};
+pub use uri_for::{UriFor, IntoUriParam, uri_for};
+pub use request::{RouteRequest, RouteError};
+pub use cors::{Cors, CorsError};
+pub use metrics::Metrics;
+#[cfg(feature = "serde")]
+pub use de::{from_params, RouteParams, DeserializeError};
 
 pub mod router;
 pub mod recognizer;
 pub mod macros;
 pub mod uri_for;
+pub mod request;
+pub mod cors;
+pub mod metrics;
+#[cfg(feature = "serde")]
+pub mod de;
@@ -41,6 +41,10 @@ macro_rules! route_line {
 
 /// Generate a URI based off of the requested one.
 ///
+/// The params are added in order, a repeated key results in a repeated query param. The values
+/// are anything convertible into a `String`, e.g. the `Segments` of a tail param, see
+/// `IntoUriParam`.
+///
 /// ```ignore
 /// uri_for!(request, "foo",
 ///          "query" => "test",
@@ -52,8 +56,8 @@ macro_rules! route_line {
 /// ```ignore
 /// ferrum_router::uri_for(&request, "foo", {
 ///     let mut params = ferrum_router:recognizer::Params::new();
-///     params.insert("query".into(), "test".into());
-///     params.insert("extraparam".into(), "param".into());
+///     params.append("query".into(), "test".into());
+///     params.append("extraparam".into(), "param".into());
 ///     params
 /// })
 /// ```
@@ -64,7 +68,7 @@ macro_rules! uri_for {
             // Underscore-prefix suppresses `unused_mut` warning
             // Also works on stable rust!
             let mut _params = $crate::Params::new();
-            $(_params.insert($key.into(), $value.into());)*
+            $(_params.append($key.into(), $crate::IntoUriParam::into_uri_param($value));)*
             _params
         })
     )
@@ -108,6 +112,7 @@ mod tests {
         let router = router!(
             get "/foo" => handler "foo",
             get "/foo/{bar}" => handler "bar",
+            get "/files/{*path}" => handler "files",
         );
 
         let mut request = Request::new(
@@ -118,7 +123,7 @@ mod tests {
         let uri: Uri = uri_for!(request, "foo",
                   "query" => "test",
                   "extraparam" => "param");
-        assert_eq!("http://www.rust-lang.org/foo?extraparam=param&query=test", uri);
+        assert_eq!("http://www.rust-lang.org/foo?query=test&extraparam=param", uri);
 
         let mut request = Request::new(
             HyperRequest::new(Method::Get, "http://www.rust-lang.org/foo/foo".parse().unwrap())
@@ -129,5 +134,18 @@ mod tests {
                   "bar" => "test",
                   "query" => "param");
         assert_eq!("http://www.rust-lang.org/foo/test?query=param", uri);
+
+        let segments = ::recognizer::Segments::from(vec!["docs", "read me.txt"]);
+        let uri: Uri = uri_for!(request, "files", "path" => &segments);
+        assert_eq!("http://www.rust-lang.org/files/docs/read%20me.txt", uri);
+        let uri: Uri = uri_for!(request, "files", "path" => segments);
+        assert_eq!("http://www.rust-lang.org/files/docs/read%20me.txt", uri);
+
+        let name = String::from("test");
+        let uri: Uri = uri_for!(request, "bar",
+                  "bar" => &name,
+                  "cow" => ::std::borrow::Cow::Borrowed("a"),
+                  "boxed" => Box::<str>::from("b"));
+        assert_eq!("http://www.rust-lang.org/foo/test?cow=a&boxed=b", uri);
     }
 }
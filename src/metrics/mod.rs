@@ -0,0 +1,158 @@
+//! Per-route request metrics, exported in the Prometheus text format.
+//!
+//! ```ignore
+//! let metrics = Metrics::new();
+//!
+//! let mut router = Router::new();
+//! router.metrics(metrics.clone());
+//! router.get("/users/{id}", user_handler, Id::some("user"));
+//! router.get("/metrics", metrics, None);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ferrum::{Request, Response, Handler, FerrumResult, Method};
+use ferrum::mime::Mime;
+
+use router::MatchedRoute;
+
+/// The upper bounds (in seconds) of the latency histogram buckets, like the Prometheus clients.
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// The request count, error count and latency histogram of the routes by request method.
+///
+/// The stats are recorded by a `Router` the metrics are set to with `Router::metrics`, around
+/// the handler of the matched route, so the requests without a route aren't counted. An error
+/// is a handler returning `Err`. The clones share the stats, and the metrics are a handler
+/// responding with them in the Prometheus text format.
+#[derive(Clone)]
+pub struct Metrics {
+    buckets: Arc<Vec<f64>>,
+    stats: Arc<Mutex<BTreeMap<(usize, String), RouteStats>>>,
+}
+
+/// The stats of a route for a request method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteStats {
+    pub route: Arc<MatchedRoute>,
+    pub requests: u64,
+    pub errors: u64,
+    /// The counts of the requests by latency bucket, not cumulative.
+    pub bucket_counts: Vec<u64>,
+    /// The total latency of the requests in seconds.
+    pub duration_sum: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics::with_buckets(DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Metrics with the latency histogram `buckets` upper bounds in seconds.
+    pub fn with_buckets(mut buckets: Vec<f64>) -> Self {
+        buckets.sort_by(|a, b| a.partial_cmp(b).expect("a bucket bound is NaN"));
+        Metrics {
+            buckets: Arc::new(buckets),
+            stats: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Record a request handled by the `route` in `duration`.
+    pub fn record(&self, route: &Arc<MatchedRoute>, method: &Method, is_error: bool, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = self.buckets.iter().position(|bound| seconds <= *bound);
+
+        let mut stats = self.stats.lock().expect("the metrics are poisoned");
+        let stats = stats.entry((route.index, method.to_string()))
+            .or_insert_with(|| RouteStats {
+                route: route.clone(),
+                requests: 0,
+                errors: 0,
+                bucket_counts: vec![0; self.buckets.len()],
+                duration_sum: 0.0,
+            });
+        stats.requests += 1;
+        if is_error {
+            stats.errors += 1;
+        }
+        if let Some(bucket) = bucket {
+            stats.bucket_counts[bucket] += 1;
+        }
+        stats.duration_sum += seconds;
+    }
+
+    /// The stats of the `route` with the index for the request `method`.
+    pub fn stats(&self, index: usize, method: &Method) -> Option<RouteStats> {
+        let stats = self.stats.lock().expect("the metrics are poisoned");
+        stats.get(&(index, method.to_string())).cloned()
+    }
+
+    /// Render the stats in the Prometheus text exposition format, by route registration order.
+    pub fn render(&self) -> String {
+        let stats = self.stats.lock().expect("the metrics are poisoned");
+        let mut text = String::new();
+
+        text.push_str("# HELP ferrum_router_requests_total The requests handled by the route.\n");
+        text.push_str("# TYPE ferrum_router_requests_total counter\n");
+        for ((_, method), route_stats) in stats.iter() {
+            let labels = labels(&route_stats.route, method);
+            let _ = writeln!(text, "ferrum_router_requests_total{{{}}} {}", labels, route_stats.requests);
+        }
+
+        text.push_str("# HELP ferrum_router_errors_total The requests the route handler failed.\n");
+        text.push_str("# TYPE ferrum_router_errors_total counter\n");
+        for ((_, method), route_stats) in stats.iter() {
+            let labels = labels(&route_stats.route, method);
+            let _ = writeln!(text, "ferrum_router_errors_total{{{}}} {}", labels, route_stats.errors);
+        }
+
+        text.push_str("# HELP ferrum_router_request_duration_seconds The latency of the route handler.\n");
+        text.push_str("# TYPE ferrum_router_request_duration_seconds histogram\n");
+        for ((_, method), route_stats) in stats.iter() {
+            let labels = labels(&route_stats.route, method);
+            let mut count = 0;
+            for (bound, bucket_count) in self.buckets.iter().zip(route_stats.bucket_counts.iter()) {
+                count += bucket_count;
+                let _ = writeln!(text, "ferrum_router_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, count);
+            }
+            let _ = writeln!(text, "ferrum_router_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, route_stats.requests);
+            let _ = writeln!(text, "ferrum_router_request_duration_seconds_sum{{{}}} {}", labels, route_stats.duration_sum);
+            let _ = writeln!(text, "ferrum_router_request_duration_seconds_count{{{}}} {}", labels, route_stats.requests);
+        }
+        text
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+impl Handler for Metrics {
+    fn handle(&self, _: &mut Request) -> FerrumResult<Response> {
+        let mime: Mime = "text/plain; version=0.0.4; charset=utf-8".parse()
+            .expect("the Prometheus content type is valid");
+        Ok(Response::new().with_content(self.render(), mime))
+    }
+}
+
+fn labels(route: &MatchedRoute, method: &str) -> String {
+    format!(
+        "route=\"{}\",id=\"{}\",method=\"{}\"",
+        escape(&route.glob),
+        escape(route.id.as_ref().map(|id| id.as_str()).unwrap_or("")),
+        escape(method)
+    )
+}
+
+/// Escape a label value of the Prometheus text format.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests;
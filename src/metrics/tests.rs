@@ -0,0 +1,98 @@
+use super::*;
+
+use std::time::Duration;
+
+use ferrum::{Method, Request, Response, FerrumResult, FerrumError, StatusCode};
+use ferrum::request::HyperRequest;
+use router::{Router, Id, NoRoute};
+
+fn route(glob: &str, id: Option<&str>, index: usize) -> Arc<MatchedRoute> {
+    Arc::new(MatchedRoute {
+        id: id.map(Id::from),
+        glob: glob.to_string(),
+        method: Some(Method::Get),
+        index,
+    })
+}
+
+#[test]
+fn test_record() {
+    let metrics = Metrics::with_buckets(vec![0.1, 0.01]);
+    let users = route("/users/{id}", Some("user"), 0);
+
+    metrics.record(&users, &Method::Get, false, Duration::from_millis(5));
+    metrics.record(&users, &Method::Get, true, Duration::from_millis(50));
+    metrics.record(&users, &Method::Get, false, Duration::from_secs(1));
+    metrics.record(&users, &Method::Head, false, Duration::from_millis(5));
+
+    let stats = metrics.stats(0, &Method::Get).unwrap();
+    assert_eq!((3, 1), (stats.requests, stats.errors));
+    assert_eq!(vec![1, 1], stats.bucket_counts);
+    assert!((stats.duration_sum - 1.055).abs() < 1e-9);
+    assert_eq!(1, metrics.stats(0, &Method::Head).unwrap().requests);
+    assert_eq!(None, metrics.stats(1, &Method::Get));
+}
+
+#[test]
+fn test_render() {
+    let metrics = Metrics::with_buckets(vec![0.01, 0.1]);
+    metrics.record(&route("/files/\"{*path}\"", None, 1), &Method::Post, true, Duration::from_millis(50));
+    metrics.record(&route("/users/{id}", Some("user"), 0), &Method::Get, false, Duration::from_millis(5));
+
+    assert_eq!(concat!(
+        "# HELP ferrum_router_requests_total The requests handled by the route.\n",
+        "# TYPE ferrum_router_requests_total counter\n",
+        "ferrum_router_requests_total{route=\"/users/{id}\",id=\"user\",method=\"GET\"} 1\n",
+        "ferrum_router_requests_total{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\"} 1\n",
+        "# HELP ferrum_router_errors_total The requests the route handler failed.\n",
+        "# TYPE ferrum_router_errors_total counter\n",
+        "ferrum_router_errors_total{route=\"/users/{id}\",id=\"user\",method=\"GET\"} 0\n",
+        "ferrum_router_errors_total{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\"} 1\n",
+        "# HELP ferrum_router_request_duration_seconds The latency of the route handler.\n",
+        "# TYPE ferrum_router_request_duration_seconds histogram\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/users/{id}\",id=\"user\",method=\"GET\",le=\"0.01\"} 1\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/users/{id}\",id=\"user\",method=\"GET\",le=\"0.1\"} 1\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/users/{id}\",id=\"user\",method=\"GET\",le=\"+Inf\"} 1\n",
+        "ferrum_router_request_duration_seconds_sum{route=\"/users/{id}\",id=\"user\",method=\"GET\"} 0.005\n",
+        "ferrum_router_request_duration_seconds_count{route=\"/users/{id}\",id=\"user\",method=\"GET\"} 1\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\",le=\"0.01\"} 0\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\",le=\"0.1\"} 1\n",
+        "ferrum_router_request_duration_seconds_bucket{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\",le=\"+Inf\"} 1\n",
+        "ferrum_router_request_duration_seconds_sum{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\"} 0.05\n",
+        "ferrum_router_request_duration_seconds_count{route=\"/files/\\\"{*path}\\\"\",id=\"\",method=\"POST\"} 1\n",
+    ), metrics.render());
+}
+
+#[test]
+fn test_router_metrics() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+    fn failing(_: &mut Request) -> FerrumResult<Response> {
+        Err(FerrumError::new(NoRoute::new(Method::Delete, "/fail"), None))
+    }
+
+    let metrics = Metrics::new();
+    let mut router = Router::new();
+    router.metrics(metrics.clone());
+    router.get("/users/{id}", handler, Id::some("user"));
+    router.any("/fail", failing, None);
+    router.get("/metrics", metrics.clone(), None);
+
+    let handle = |method: Method, uri: &str| {
+        let mut request = Request::new(HyperRequest::new(method, uri.parse().unwrap()));
+        router.handle(&mut request)
+    };
+    assert!(handle(Method::Get, "http://localhost/users/1").is_ok());
+    assert!(handle(Method::Get, "http://localhost/users/2").is_ok());
+    assert!(handle(Method::Delete, "http://localhost/fail").is_err());
+    assert!(handle(Method::Get, "http://localhost/missing").is_err());
+
+    let response = handle(Method::Get, "http://localhost/metrics").unwrap();
+    assert_eq!(StatusCode::Ok, response.status);
+
+    assert_eq!((2, 0), metrics.stats(0, &Method::Get).map(|stats| (stats.requests, stats.errors)).unwrap());
+    assert_eq!((1, 1), metrics.stats(1, &Method::Delete).map(|stats| (stats.requests, stats.errors)).unwrap());
+    assert_eq!(1, metrics.stats(2, &Method::Get).unwrap().requests);
+    assert!(metrics.render().contains("ferrum_router_requests_total{route=\"/fail\",id=\"\",method=\"DELETE\"} 1\n"));
+}
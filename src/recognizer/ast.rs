@@ -0,0 +1,727 @@
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+use std::str;
+
+use regex::{self, Regex};
+
+use recognizer::error::{GlobError, GlobErrorKind};
+use recognizer::types::{Store, Type, TypeName, TypePattern};
+
+/// A parsed glob, which can be inspected by tools and displayed back as the glob text.
+///
+/// ```ignore
+/// let ast = GlobAst::parse("/posts/{id:number}[/{*path}]", &types, false)?;
+/// assert_eq!("/posts/{id:number}[/{*path}]", ast.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobAst {
+    pub nodes: Vec<GlobNode>,
+    /// The literal text is a regex, see `Glob::raw_regex`.
+    pub raw_regex: bool,
+    /// The glob text, which the spans of the nodes refer to.
+    pub source: String,
+}
+
+/// The regex of a glob, along with the information about its params.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobRegex {
+    /// The regex source matching the whole path.
+    pub pattern: String,
+    /// The glob type names of the named params, including the nested ones.
+    pub param_types: Vec<(String, String)>,
+    /// The regex group names of the captures nested in the composed types and the names of
+    /// their params, like `date__year` and `date.year`.
+    pub nested_params: Vec<(String, String)>,
+    /// The regex source of each top-level node of the glob, see `Router::explain`.
+    pub node_patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobNode {
+    /// The text matched literally, unescaped, or the regex text with the raw regex syntax.
+    Literal(String),
+    /// A `{name}`, `{name:type}` or `{name:pattern}` param, the name may be omitted if typed.
+    Param(ParamNode),
+    /// A `{*name}` param capturing the rest of the path.
+    Tail(TailNode),
+    /// A `[...]` section, which may be absent in the path.
+    Optional(Vec<GlobNode>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamNode {
+    pub name: Option<String>,
+    pub kind: ParamKind,
+    /// The byte range of the param in the glob.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamKind {
+    /// Typed by the glob type with the name of the param if it applies by name (see
+    /// `Type::applies_by_name`), or as a `string`.
+    Default,
+    /// Typed by a glob type.
+    Type(String),
+    /// Matched by an inline regex.
+    Pattern(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TailNode {
+    pub name: String,
+    /// The byte range of the param in the glob.
+    pub span: Range<usize>,
+}
+
+impl GlobAst {
+    /// Parse the glob, resolving the param types in `types`.
+    ///
+    /// See `Recognizer::parse_glob` for the syntax, with `raw_regex` the literal text is
+    /// kept as a regex and `[...]` isn't an optional section.
+    pub fn parse<G, N, P>(glob: G, types: &Store<N, P>, raw_regex: bool) -> Result<GlobAst, GlobError>
+        where G: AsRef<[u8]>,
+              N: TypeName,
+              P: TypePattern
+    {
+        let glob = glob.as_ref();
+        let error = |kind, span: Range<usize>| GlobError::new(kind, glob, span);
+
+        if let Err(utf8_error) = str::from_utf8(glob) {
+            let start = utf8_error.valid_up_to();
+            let end = start + utf8_error.error_len().unwrap_or(glob.len() - start);
+            return Err(error(GlobErrorKind::InvalidUtf8, start..end));
+        }
+
+        // The nodes of the enclosing optional sections and the positions of their `[`.
+        let mut sections = Vec::<(Vec<GlobNode>, usize)>::new();
+        let mut nodes = Vec::new();
+        let mut literal = Vec::new();
+        let mut names = Vec::<String>::new();
+
+        let mut index = 0;
+        while index < glob.len() {
+            let bch = glob[index];
+            match bch {
+                b'{' if !raw_regex || index == 0 || glob[index - 1] != b'\\' => {
+                    push_literal(&mut nodes, &mut literal);
+                    let (node, end) = parse_param(glob, index, types, &mut names)
+                        .map_err(|(kind, span)| error(kind, span))?;
+                    nodes.push(node);
+                    index = end;
+                    continue;
+                },
+                b'}' if !raw_regex => return Err(error(GlobErrorKind::UnmatchedClose, index..index + 1)),
+                b'[' if !raw_regex => {
+                    push_literal(&mut nodes, &mut literal);
+                    sections.push((mem::take(&mut nodes), index));
+                },
+                b']' if !raw_regex => {
+                    push_literal(&mut nodes, &mut literal);
+                    match sections.pop() {
+                        Some((outer, _)) => {
+                            let section = mem::replace(&mut nodes, outer);
+                            nodes.push(GlobNode::Optional(section));
+                        },
+                        None => return Err(error(GlobErrorKind::UnmatchedOptionalClose, index..index + 1)),
+                    }
+                },
+                _ if raw_regex => literal.push(bch),
+                b'\\' if index + 1 < glob.len() => {
+                    literal.push(glob[index + 1]);
+                    index += 1;
+                },
+                _ => literal.push(bch),
+            }
+            index += 1;
+        }
+        push_literal(&mut nodes, &mut literal);
+
+        if let Some(&(_, start)) = sections.last() {
+            return Err(error(GlobErrorKind::UnclosedOptional, start..glob.len()));
+        }
+        Ok(GlobAst {
+            nodes,
+            raw_regex,
+            source: String::from_utf8_lossy(glob).into_owned(),
+        })
+    }
+
+    /// The regex of the glob with its param types resolved in `types`.
+    ///
+    /// Fails if a type of the glob refers to a missing or cyclic type in `types`, which is
+    /// checked by `parse` with its types.
+    pub fn regex<N, P>(&self, types: &Store<N, P>) -> Result<GlobRegex, GlobError>
+        where N: TypeName,
+              P: TypePattern
+    {
+        let mut glob_regex = GlobRegex {
+            pattern: "^".to_string(),
+            ..GlobRegex::default()
+        };
+        let mut pattern = String::new();
+        for node in self.nodes.iter() {
+            let mut node_pattern = String::new();
+            push_pattern(::std::slice::from_ref(node), self.raw_regex, types, &mut node_pattern, &mut glob_regex)
+                .map_err(|(kind, span)| GlobError::new(kind, self.source.as_bytes(), span))?;
+            pattern += &node_pattern;
+            glob_regex.node_patterns.push(node_pattern);
+        }
+
+        glob_regex.pattern += &pattern;
+        glob_regex.pattern += if pattern.ends_with('/') { "$" } else { "/?$" };
+        Ok(glob_regex)
+    }
+
+    /// The names of the params (including the tail ones) in the glob order.
+    pub fn param_names(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for_each_param(&self.nodes, &mut |node| match *node {
+            GlobNode::Param(ParamNode { name: Some(ref name), .. }) => names.push(name.as_str()),
+            GlobNode::Tail(ref tail) => names.push(tail.name.as_str()),
+            _ => {},
+        });
+        names
+    }
+}
+
+impl ParamNode {
+    /// The glob type name (if the param is typed by a glob type) and the regex of the param.
+    pub fn resolve<'a, N, P>(&'a self, types: &'a Store<N, P>) -> (Option<&'a str>, &'a str)
+        where N: TypeName,
+              P: TypePattern
+    {
+        match self.kind {
+            ParamKind::Type(ref type_name) => match types.get(type_name.as_str()) {
+                Some(pattern) => (Some(type_name.as_str()), pattern.as_ref()),
+                None => (None, type_name.as_str()),
+            },
+            ParamKind::Pattern(ref pattern) => (None, pattern.as_str()),
+            ParamKind::Default => name_type(self.name.as_deref().unwrap_or(""), types),
+        }
+    }
+}
+
+/// The glob type name and the regex of a param without an explicit type: the type with the
+/// name of the param if it applies by name (see `Type::applies_by_name`), or `string`.
+fn name_type<'a, N, P>(name: &'a str, types: &'a Store<N, P>) -> (Option<&'a str>, &'a str)
+    where N: TypeName,
+          P: TypePattern
+{
+    match types.get(name) {
+        Some(pattern) if Type::applies_by_name(name, pattern.as_ref()) => (Some(name), pattern.as_ref()),
+        _ => (Some(Type::STRING_NAME), Type::STRING_PATTERN),
+    }
+}
+
+impl GlobNode {
+    /// The glob text of the node, with the raw regex syntax if `raw_regex`.
+    pub fn to_glob(&self, raw_regex: bool) -> String {
+        struct NodeGlob<'a>(&'a GlobNode, bool);
+
+        impl<'a> fmt::Display for NodeGlob<'a> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_node(f, self.0, self.1)
+            }
+        }
+
+        NodeGlob(self, raw_regex).to_string()
+    }
+}
+
+impl fmt::Display for GlobAst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for node in self.nodes.iter() {
+            write_node(f, node, self.raw_regex)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParamNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("{")?;
+        if let Some(ref name) = self.name {
+            f.write_str(name)?;
+        }
+        match self.kind {
+            ParamKind::Default => {},
+            ParamKind::Type(ref text) | ParamKind::Pattern(ref text) => write!(f, ":{}", text)?,
+        }
+        f.write_str("}")
+    }
+}
+
+impl fmt::Display for TailNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{*{}}}", self.name)
+    }
+}
+
+fn write_node(f: &mut fmt::Formatter, node: &GlobNode, raw_regex: bool) -> fmt::Result {
+    match *node {
+        GlobNode::Literal(ref text) if raw_regex => f.write_str(text),
+        GlobNode::Literal(ref text) => {
+            for ch in text.chars() {
+                if let '\\' | '{' | '}' | '[' | ']' = ch {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{}", ch)?;
+            }
+            Ok(())
+        },
+        GlobNode::Param(ref param) => write!(f, "{}", param),
+        GlobNode::Tail(ref tail) => write!(f, "{}", tail),
+        GlobNode::Optional(ref nodes) => {
+            f.write_str("[")?;
+            for node in nodes.iter() {
+                write_node(f, node, raw_regex)?;
+            }
+            f.write_str("]")
+        },
+    }
+}
+
+fn push_pattern<N, P>(
+    nodes: &[GlobNode],
+    raw_regex: bool,
+    types: &Store<N, P>,
+    pattern: &mut String,
+    glob_regex: &mut GlobRegex
+) -> Result<(), (GlobErrorKind, Range<usize>)>
+    where N: TypeName,
+          P: TypePattern
+{
+    for node in nodes.iter() {
+        match *node {
+            GlobNode::Literal(ref text) if raw_regex => pattern.push_str(text),
+            GlobNode::Literal(ref text) => pattern.push_str(&regex::escape(text)),
+            GlobNode::Param(ref param) => {
+                let (type_name, regex_type) = param.resolve(types);
+                let capture = param.name.as_ref().map(|name| (name.as_str(), name.as_str()));
+                let mut stack = type_name.iter().map(|type_name| type_name.to_string()).collect();
+                let regex_type = expand_pattern(regex_type, types, capture, &mut stack, glob_regex)
+                    .map_err(|kind| (kind, param.span.clone()))?;
+
+                match param.name {
+                    Some(ref name) => {
+                        if let Some(type_name) = type_name {
+                            glob_regex.param_types.push((name.clone(), type_name.to_string()));
+                        }
+                        pattern.push_str(&format!("(?P<{}>{})", name, regex_type));
+                    },
+                    None => pattern.push_str(&format!("({})", regex_type)),
+                }
+            },
+            GlobNode::Tail(ref tail) => pattern.push_str(&format!("(?P<{}>.*)", tail.name)),
+            GlobNode::Optional(ref nodes) => {
+                pattern.push_str("(?:");
+                push_pattern(nodes, raw_regex, types, pattern, glob_regex)?;
+                pattern.push_str(")?");
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Expand the references to the glob types in the regex of a param.
+///
+/// A reference has the param syntax, `{name:type}` or `{name}` contributes a nested capture of
+/// the param `parent.name` (if the `capture` group and param names are given) and `{:type}`
+/// doesn't. The types being expanded are kept in the `stack` to detect the cycles.
+// `Option::is_some_and` needs Rust 1.70.
+#[allow(unknown_lints, clippy::unnecessary_map_or)]
+fn expand_pattern<N, P>(
+    pattern: &str,
+    types: &Store<N, P>,
+    capture: Option<(&str, &str)>,
+    stack: &mut Vec<String>,
+    glob_regex: &mut GlobRegex
+) -> Result<String, GlobErrorKind>
+    where N: TypeName,
+          P: TypePattern
+{
+    let bytes = pattern.as_bytes();
+    let mut expanded = String::new();
+    let mut copied = 0;
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let is_reference = bytes[index] == b'{' && bytes.get(index + 1)
+            .map_or(false, |&next| next == b':' || next == b'_' || next.is_ascii_alphabetic());
+        if bytes[index] == b'\\' {
+            // The braces of an escape like `\p{L}` or `\x{1F600}` aren't a reference.
+            let is_letter = matches!(bytes.get(index + 1), Some(byte) if byte.is_ascii_alphabetic());
+            index += 2;
+            if is_letter && bytes.get(index) == Some(&b'{') {
+                index = pattern[index..].find('}').map_or(bytes.len(), |close| index + close + 1);
+            }
+            continue;
+        } else if !is_reference {
+            index += 1;
+            continue;
+        }
+
+        let end = scan_param(bytes, index).ok_or(GlobErrorKind::UnclosedParam)?.end;
+        expanded.push_str(&pattern[copied..index]);
+        let reference = &pattern[index + 1..end - 1];
+        let (name, type_text) = match reference.find(':') {
+            Some(colon) => (&reference[..colon], Some(&reference[colon + 1..])),
+            None => (reference, None),
+        };
+        if !name.is_empty() && !is_identifier(name) {
+            return Err(GlobErrorKind::InvalidName(name.to_string()));
+        }
+
+        let (type_name, regex_type) = match type_text {
+            Some("") => return Err(GlobErrorKind::EmptyType),
+            Some(type_text) => match types.get(type_text) {
+                Some(regex_type) => (Some(type_text), regex_type.as_ref()),
+                None => (None, type_text),
+            },
+            None => name_type(name, types),
+        };
+        if let Some(type_name) = type_name {
+            if stack.iter().any(|other| other == type_name) {
+                let mut cycle = stack.clone();
+                cycle.push(type_name.to_string());
+                return Err(GlobErrorKind::TypeCycle(cycle));
+            }
+            stack.push(type_name.to_string());
+        }
+
+        let nested = match capture {
+            Some((group, param)) if !name.is_empty() => {
+                Some((format!("{}__{}", group, name), format!("{}.{}", param, name)))
+            },
+            _ => None,
+        };
+        let nested_capture = nested.as_ref().map(|(group, param)| (group.as_str(), param.as_str()));
+        let regex_type = expand_pattern(regex_type, types, nested_capture, stack, glob_regex)?;
+        if type_name.is_some() {
+            stack.pop();
+        }
+
+        match nested {
+            Some((group, param)) => {
+                expanded.push_str(&format!("(?P<{}>{})", group, regex_type));
+                if let Some(type_name) = type_name {
+                    glob_regex.param_types.push((param.clone(), type_name.to_string()));
+                }
+                glob_regex.nested_params.push((group, param));
+            },
+            None => expanded.push_str(&format!("(?:{})", regex_type)),
+        }
+        index = end;
+        copied = end;
+    }
+    expanded.push_str(&pattern[copied..]);
+    Ok(expanded)
+}
+
+/// Call `f` with every param and tail node, including the ones in the optional sections.
+pub fn for_each_param<'a, F>(nodes: &'a [GlobNode], f: &mut F)
+    where F: FnMut(&'a GlobNode)
+{
+    for node in nodes.iter() {
+        match *node {
+            GlobNode::Param(_) | GlobNode::Tail(_) => f(node),
+            GlobNode::Optional(ref nodes) => for_each_param(nodes, f),
+            GlobNode::Literal(_) => {},
+        }
+    }
+}
+
+/// Move the accumulated `literal` glob text into a literal node.
+///
+/// The glob is checked to be valid UTF-8 before, so the literal text is too.
+fn push_literal(nodes: &mut Vec<GlobNode>, literal: &mut Vec<u8>) {
+    if !literal.is_empty() {
+        let text = String::from_utf8_lossy(&literal.split_off(0)).into_owned();
+        nodes.push(GlobNode::Literal(text));
+    }
+}
+
+type ParamResult = Result<(GlobNode, usize), (GlobErrorKind, Range<usize>)>;
+
+/// Parse the param starting with `{` at `start`, with the end of the param after `}`.
+fn parse_param<N, P>(glob: &[u8], start: usize, types: &Store<N, P>, names: &mut Vec<String>) -> ParamResult
+    where N: TypeName,
+          P: TypePattern
+{
+    let param = scan_param(glob, start)
+        .ok_or((GlobErrorKind::UnclosedParam, start..glob.len()))?;
+    let end = param.end;
+    let span = start..end;
+    let mut name = String::from_utf8_lossy(&param.name).into_owned();
+    let mut name_span = param.name_span;
+
+    let is_tail = name.starts_with('*');
+    if is_tail {
+        name.remove(0);
+        name_span.start += 1;
+        if let Some((_, type_span)) = param.pattern {
+            return Err((GlobErrorKind::TypedTail, type_span));
+        }
+        if name.is_empty() {
+            return Err((GlobErrorKind::EmptyParam, span));
+        }
+    }
+
+    let kind = match param.pattern {
+        Some((ref text, _)) if text.is_empty() && name.is_empty() => return Err((GlobErrorKind::EmptyParam, span)),
+        Some((ref text, ref type_span)) if text.is_empty() => return Err((GlobErrorKind::EmptyType, type_span.clone())),
+        Some((ref text, _)) => {
+            let text = String::from_utf8_lossy(text).into_owned();
+            if types.contains_key(text.as_str()) {
+                ParamKind::Type(text)
+            } else {
+                ParamKind::Pattern(text)
+            }
+        },
+        None if name.is_empty() => return Err((GlobErrorKind::EmptyParam, span)),
+        None => ParamKind::Default,
+    };
+
+    if !name.is_empty() {
+        if !is_identifier(&name) {
+            return Err((GlobErrorKind::InvalidName(name), name_span));
+        }
+        if names.contains(&name) {
+            return Err((GlobErrorKind::DuplicateName(name), name_span));
+        }
+        names.push(name.clone());
+    }
+
+    if is_tail {
+        return Ok((GlobNode::Tail(TailNode { name, span }), end));
+    }
+
+    let param_node = ParamNode {
+        name: if name.is_empty() { None } else { Some(name) },
+        kind,
+        span: span.clone(),
+    };
+    let type_span = param.pattern.map(|(_, type_span)| type_span).unwrap_or(span);
+    let (type_name, regex_type) = param_node.resolve(types);
+    let mut stack = type_name.iter().map(|type_name| type_name.to_string()).collect();
+    let capture = param_node.name.as_ref().map(|name| (name.as_str(), name.as_str()));
+    let regex_type = expand_pattern(regex_type, types, capture, &mut stack, &mut GlobRegex::default())
+        .map_err(|kind| (kind, type_span.clone()))?;
+    if let Err(regex_error) = Regex::new(&regex_type) {
+        return Err((GlobErrorKind::InvalidPattern(regex_error.to_string()), type_span));
+    }
+    Ok((GlobNode::Param(param_node), end))
+}
+
+/// The param of a glob with the whitespace removed and the byte ranges of its parts.
+struct ParamSyntax {
+    /// The end of the param after the closing `}`.
+    end: usize,
+    name: Vec<u8>,
+    name_span: Range<usize>,
+    /// The type after `:` (which may be empty) and its range, or the range of `:` if it's empty.
+    pattern: Option<(Vec<u8>, Range<usize>)>,
+}
+
+/// Scan the param starting with `{` at `start` up to the matching `}`, `None` if it's unclosed.
+///
+/// The braces inside of the param type are balanced, so `{id:[0-9]{4}}` is a single param.
+fn scan_param(glob: &[u8], start: usize) -> Option<ParamSyntax> {
+    let mut param = ParamSyntax {
+        end: start,
+        name: Vec::new(),
+        name_span: start..start + 1,
+        pattern: None,
+    };
+    let mut depth = 0;
+
+    let mut index = start + 1;
+    while index < glob.len() {
+        let mut len = 1;
+        match glob[index] {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                index += 1;
+                continue;
+            },
+            b'}' if depth == 0 => {
+                param.end = index + 1;
+                return Some(param);
+            },
+            b':' if param.pattern.is_none() => {
+                param.pattern = Some((Vec::new(), index..index + 1));
+                index += 1;
+                continue;
+            },
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            b'\\' if index + 1 < glob.len() => len = 2,
+            _ => {},
+        }
+
+        let (text, span) = match param.pattern {
+            Some((ref mut text, ref mut span)) => (text, span),
+            None => (&mut param.name, &mut param.name_span),
+        };
+        if text.is_empty() {
+            span.start = index;
+        }
+        text.extend_from_slice(&glob[index..index + len]);
+        span.end = index + len;
+        index += len;
+    }
+    None
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    match bytes.next() {
+        Some(first) if first == b'_' || first.is_ascii_alphabetic() => {
+            bytes.all(|byte| byte == b'_' || byte.is_ascii_alphanumeric())
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use recognizer::types::{DefaultStore, DefaultStoreBuild};
+
+    #[test]
+    fn parse_nodes() {
+        let types = DefaultStore::with_default_types();
+
+        let ast = GlobAst::parse(r"/posts/{id:number}/{slug:[a-z\-]+}[/{*rest}]/\[{:x}\]", &types, false).unwrap();
+        assert_eq!(vec![
+            GlobNode::Literal("/posts/".to_string()),
+            GlobNode::Param(ParamNode { name: Some("id".to_string()), kind: ParamKind::Type("number".to_string()), span: 7..18 }),
+            GlobNode::Literal("/".to_string()),
+            GlobNode::Param(ParamNode { name: Some("slug".to_string()), kind: ParamKind::Pattern(r"[a-z\-]+".to_string()), span: 19..34 }),
+            GlobNode::Optional(vec![
+                GlobNode::Literal("/".to_string()),
+                GlobNode::Tail(TailNode { name: "rest".to_string(), span: 36..43 }),
+            ]),
+            GlobNode::Literal("/[".to_string()),
+            GlobNode::Param(ParamNode { name: None, kind: ParamKind::Pattern("x".to_string()), span: 47..51 }),
+            GlobNode::Literal("]".to_string()),
+        ], ast.nodes);
+        assert_eq!(vec!["id", "slug", "rest"], ast.param_names());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let types = DefaultStore::with_default_types();
+
+        let globs = vec![
+            "",
+            "/posts/{id}",
+            "/posts/{ id : number }.json",
+            r"/set/\{{name}\}/\\/\[x\]",
+            "/archive/{year:number}[/{month:number}[/{day:number}]]",
+            "/files/{*path}",
+            "/{:[0-9]{2}}/привет",
+        ];
+        for glob in globs {
+            let ast = GlobAst::parse(glob, &types, false).unwrap();
+            let text = ast.to_string();
+            let reparsed = GlobAst::parse(&text, &types, false).unwrap();
+            assert_eq!(text, reparsed.to_string(), "{}", glob);
+            assert_eq!(ast.param_names(), reparsed.param_names(), "{}", glob);
+        }
+        let ast = GlobAst::parse("/posts/{ id : number }.json", &types, false).unwrap();
+        assert_eq!("/posts/{id:number}.json", ast.to_string());
+
+        let ast = GlobAst::parse(r"/files/.*\.(png|jpg)/\{{name}\}", &types, true).unwrap();
+        assert_eq!(r"/files/.*\.(png|jpg)/\{{name}\}", ast.to_string());
+    }
+
+    #[test]
+    fn regex_pattern() {
+        let types = DefaultStore::with_default_types();
+
+        let ast = GlobAst::parse("/a.b/{id:number}[/{name}]/{*tail}", &types, false).unwrap();
+        let glob_regex = ast.regex(&types).unwrap();
+        assert_eq!(r"^/a\.b/(?P<id>[0-9]+)(?:/(?P<name>[^/.]+))?/(?P<tail>.*)/?$", glob_regex.pattern);
+        assert_eq!(vec![
+            ("id".to_string(), "number".to_string()),
+            ("name".to_string(), "string".to_string()),
+        ], glob_regex.param_types);
+        assert!(glob_regex.nested_params.is_empty());
+        assert_eq!(vec![
+            r"/a\.b/",
+            "(?P<id>[0-9]+)",
+            "(?:/(?P<name>[^/.]+))?",
+            "/",
+            "(?P<tail>.*)",
+        ], glob_regex.node_patterns);
+        assert_eq!("[/{name}]", ast.nodes[2].to_glob(false));
+    }
+
+    #[test]
+    fn composed_types() {
+        let mut types = DefaultStore::with_default_types();
+        types.insert("ymd", "{year:number}-{month:[0-9]{2}}-{:number}");
+        types.insert("range", r"{from:ymd}\.\.{to:ymd}");
+
+        let ast = GlobAst::parse("/{r:range}/{:ymd}", &types, false).unwrap();
+        let glob_regex = ast.regex(&types).unwrap();
+        assert_eq!(concat!(
+            r"^/(?P<r>(?P<r__from>(?P<r__from__year>[0-9]+)-(?P<r__from__month>[0-9]{2})-(?:[0-9]+))",
+            r"\.\.(?P<r__to>(?P<r__to__year>[0-9]+)-(?P<r__to__month>[0-9]{2})-(?:[0-9]+)))",
+            r"/((?:[0-9]+)-(?:[0-9]{2})-(?:[0-9]+))/?$",
+        ), glob_regex.pattern);
+        assert_eq!(vec![
+            ("r__from__year".to_string(), "r.from.year".to_string()),
+            ("r__from__month".to_string(), "r.from.month".to_string()),
+            ("r__from".to_string(), "r.from".to_string()),
+            ("r__to__year".to_string(), "r.to.year".to_string()),
+            ("r__to__month".to_string(), "r.to.month".to_string()),
+            ("r__to".to_string(), "r.to".to_string()),
+        ], glob_regex.nested_params);
+        assert!(glob_regex.param_types.contains(&("r.from.year".to_string(), "number".to_string())));
+        assert!(glob_regex.param_types.contains(&("r".to_string(), "range".to_string())));
+
+        types.insert("a", "x{:b}");
+        types.insert("b", "{c}");
+        types.insert("c", "{:a}|y");
+        let error = GlobAst::parse("/{id:a}", &types, false).unwrap_err();
+        assert_eq!(GlobErrorKind::TypeCycle(vec![
+            "a".to_string(), "b".to_string(), "c".to_string(), "a".to_string(),
+        ]), error.kind);
+        assert_eq!(5..6, error.span);
+
+        types.insert("self", "{:self}");
+        assert!(GlobAst::parse("/{self}", &types, false).is_err());
+        types.insert("bad", "{user-id:number}");
+        assert_eq!(GlobErrorKind::InvalidName("user-id".to_string()), GlobAst::parse("/{:bad}", &types, false).unwrap_err().kind);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let types = DefaultStore::with_default_types();
+        let error = |glob: &str| GlobAst::parse(glob, &types, false).unwrap_err();
+
+        assert_eq!((GlobErrorKind::UnclosedOptional, 5..11), {
+            let error = error("/list[/page");
+            (error.kind, error.span)
+        });
+        assert_eq!((GlobErrorKind::UnmatchedOptionalClose, 5..6), {
+            let error = error("/list]");
+            (error.kind, error.span)
+        });
+        assert_eq!((GlobErrorKind::TypedTail, 14..16), {
+            let error = error("/files/{*path:.*}");
+            (error.kind, error.span)
+        });
+        assert_eq!((GlobErrorKind::EmptyParam, 7..10), {
+            let error = error("/files/{*}");
+            (error.kind, error.span)
+        });
+        assert_eq!((GlobErrorKind::DuplicateName("id".to_string()), 9..11), {
+            let error = error("/{id}[/{*id}]");
+            (error.kind, error.span)
+        });
+    }
+}
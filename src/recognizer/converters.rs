@@ -0,0 +1,439 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ferrum::typemap::Key;
+use url::percent_encoding::{percent_decode, utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+use recognizer::types::Type;
+
+/// A value converted from a matched param.
+pub type TypedValue = Box<dyn Any + Send + Sync>;
+
+/// Converts a matched param, percent-decoded, into a typed value. `None` means that the param
+/// doesn't represent a valid value and the route doesn't match, except for the default
+/// converters, see `Converters::is_default_for`.
+pub type Converter = Arc<dyn Fn(&str) -> Option<TypedValue> + Send + Sync>;
+
+/// Converters of the params, specialized by glob type name.
+#[derive(Clone, Default)]
+pub struct Converters {
+    converters: HashMap<String, Converter>,
+    /// The built-in patterns of the types which still have their default converter.
+    default_patterns: HashMap<String, &'static str>,
+}
+
+impl Converters {
+    pub fn new() -> Self {
+        Converters::default()
+    }
+
+    /// Construct converters for the default glob types:
+    /// `number` into `u64`, `int` into `i64`, `uuid` into `Uuid` and `date` into `Date`.
+    ///
+    /// They only apply to the types which patterns are the built-in ones, see `get_for`.
+    pub fn with_default_converters() -> Self {
+        let mut converters = Converters::new();
+        converters.insert_from_str::<u64>(Type::NUMBER_NAME);
+        converters.insert_from_str::<i64>(Type::INT_NAME);
+        converters.insert_from_str::<Uuid>(Type::UUID_NAME);
+        converters.insert_from_str::<Date>(Type::DATE_NAME);
+        converters.default_patterns = [
+            (Type::NUMBER_NAME, Type::NUMBER_PATTERN),
+            (Type::INT_NAME, Type::INT_PATTERN),
+            (Type::UUID_NAME, Type::UUID_PATTERN),
+            (Type::DATE_NAME, Type::DATE_PATTERN),
+        ].iter().map(|&(name, pattern)| (name.to_string(), pattern)).collect();
+        converters
+    }
+
+    /// Register a converter for the glob type `name`.
+    pub fn insert<S, F>(&mut self, name: S, converter: F) -> &mut Self
+        where S: Into<String>,
+              F: Fn(&str) -> Option<TypedValue> + Send + Sync + 'static
+    {
+        let name = name.into();
+        self.default_patterns.remove(&name);
+        self.converters.insert(name, Arc::new(converter));
+        self
+    }
+
+    /// Register a converter for the glob type `name`, which parses the params into `T`.
+    pub fn insert_from_str<T>(&mut self, name: &str) -> &mut Self
+        where T: FromStr + Any + Send + Sync
+    {
+        self.insert(name, |value| T::from_str(value).ok().map(|value| Box::new(value) as TypedValue))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Converter> {
+        self.converters.get(name)
+    }
+
+    /// Get the converter of the glob type `name` matching the `pattern`. A default converter
+    /// only applies to the built-in pattern of its type, the values of a redefined type may
+    /// not parse into the default converter type.
+    pub fn get_for(&self, name: &str, pattern: &str) -> Option<&Converter> {
+        match self.default_patterns.get(name) {
+            Some(default_pattern) if *default_pattern != pattern => None,
+            _ => self.converters.get(name),
+        }
+    }
+
+    /// Check whether the converter of the glob type `name` matching the `pattern` is a default
+    /// one. A value matching the built-in pattern which the default converter rejects, e.g. a
+    /// `number` above `u64::MAX` or the `date` 2018-02-30, results in a `ParamError` and a
+    /// BadRequest response instead of a route miss.
+    pub fn is_default_for(&self, name: &str, pattern: &str) -> bool {
+        self.default_patterns.get(name).copied() == Some(pattern)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.converters.contains_key(name)
+    }
+
+    /// Add all converters of `other`, replacing the ones with the same names.
+    pub fn extend(&mut self, other: &Converters) {
+        for (name, converter) in other.converters.iter() {
+            self.converters.insert(name.clone(), converter.clone());
+            match other.default_patterns.get(name) {
+                Some(pattern) => self.default_patterns.insert(name.clone(), pattern),
+                None => self.default_patterns.remove(name),
+            };
+        }
+    }
+}
+
+impl fmt::Debug for Converters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.converters.keys()).finish()
+    }
+}
+
+/// The converted values of the params, which glob types have converters.
+///
+/// ```ignore
+/// let id = request.extensions.get::<TypedParams>().unwrap().get::<u64>("id");
+/// ```
+#[derive(Default)]
+pub struct TypedParams {
+    values: HashMap<String, TypedValue>,
+}
+
+impl TypedParams {
+    pub fn new() -> Self {
+        TypedParams::default()
+    }
+
+    /// Get the converted value of the param `name`, if it has the type `T`.
+    pub fn get<T: Any>(&self, name: &str) -> Option<&T> {
+        self.values.get(name).and_then(|value| value.downcast_ref())
+    }
+
+    pub fn insert<S: Into<String>>(&mut self, name: S, value: TypedValue) {
+        self.values.insert(name.into(), value);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for TypedParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
+impl Key for TypedParams {
+    type Value = TypedParams;
+}
+
+/// The error of parsing a `Uuid` or a `Date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseValueError(&'static str);
+
+impl fmt::Display for ParseValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// A UUID converted from the `uuid` glob type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Uuid(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = ParseValueError;
+
+    /// Parse the hyphenated form, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const ERROR: ParseValueError = ParseValueError("invalid UUID");
+
+        let groups = value.split('-').collect::<Vec<_>>();
+        let lengths = groups.iter().map(|group| group.len()).collect::<Vec<_>>();
+        if lengths != [8, 4, 4, 4, 12] {
+            return Err(ERROR);
+        }
+
+        let digits = groups.concat();
+        if !digits.bytes().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(ERROR);
+        }
+        let mut bytes = [0; 16];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            let hex = digits.get(index * 2 .. index * 2 + 2).ok_or(ERROR)?;
+            *byte = u8::from_str_radix(hex, 16).map_err(|_| ERROR)?;
+        }
+        Ok(Uuid(bytes))
+    }
+}
+
+impl fmt::Display for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, byte) in self.0.iter().enumerate() {
+            if index == 4 || index == 6 || index == 8 || index == 10 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// A calendar date converted from the `date` glob type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Construct a date, if it exists in the proleptic Gregorian calendar.
+    // `u16::is_multiple_of` needs Rust 1.87.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        if day >= 1 && day <= days {
+            Some(Date { year, month, day })
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseValueError;
+
+    /// Parse the ISO 8601 calendar date form, e.g. `2018-02-28`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const ERROR: ParseValueError = ParseValueError("invalid date");
+
+        let parts = value.split('-').collect::<Vec<_>>();
+        if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+            return Err(ERROR);
+        }
+        if !parts.iter().all(|part| part.bytes().all(|byte| byte.is_ascii_digit())) {
+            return Err(ERROR);
+        }
+
+        let year = parts[0].parse().map_err(|_| ERROR)?;
+        let month = parts[1].parse().map_err(|_| ERROR)?;
+        let day = parts[2].parse().map_err(|_| ERROR)?;
+        Date::new(year, month, day).ok_or(ERROR)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The percent-decoded segments captured by a `{*name}` tail param.
+///
+/// It is displayed as the percent-encoded path, so it can be passed back to `uri_for`:
+///
+/// ```ignore
+/// let path = request.extensions.get::<TypedParams>().unwrap().get::<Segments>("path").unwrap();
+/// params.insert("path".to_string(), path.clone().into());
+/// let uri = uri_for!(request, "files", "path" => path.clone());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Segments(Vec<String>);
+
+impl Segments {
+    pub fn new(segments: Vec<String>) -> Self {
+        Segments(segments)
+    }
+
+    /// Split the `path` into the decoded segments, skipping the empty and the `.` ones.
+    ///
+    /// `None` if a decoded segment isn't valid UTF-8, contains a `/`, a `\\` or a NUL (which
+    /// would let it span several segments of a file path), or is `..` unless `allow_parent`
+    /// is set.
+    pub fn parse(path: &str, allow_parent: bool) -> Option<Self> {
+        let mut segments = Vec::new();
+        for segment in path.split('/') {
+            let segment = percent_decode(segment.as_bytes()).decode_utf8().ok()?;
+            if segment.contains(&['/', '\\', '\0'][..]) {
+                return None;
+            }
+            match segment.as_ref() {
+                "" | "." => {},
+                ".." if !allow_parent => return None,
+                _ => segments.push(segment.into_owned()),
+            }
+        }
+        Some(Segments(segments))
+    }
+
+    pub fn into_vec(self) -> Vec<String> {
+        self.0
+    }
+}
+
+impl Deref for Segments {
+    type Target = [String];
+
+    fn deref(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl<S: Into<String>> From<Vec<S>> for Segments {
+    fn from(segments: Vec<S>) -> Self {
+        Segments(segments.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Segments> for String {
+    fn from(segments: Segments) -> String {
+        segments.to_string()
+    }
+}
+
+impl<'a> From<&'a Segments> for String {
+    fn from(segments: &'a Segments) -> String {
+        segments.to_string()
+    }
+}
+
+impl fmt::Display for Segments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, segment) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str("/")?;
+            }
+            write!(f, "{}", utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uuid() {
+        let uuid = Uuid::from_str("67E55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(0x67, uuid.as_bytes()[0]);
+        assert_eq!(0xc8, uuid.as_bytes()[15]);
+        assert_eq!("67e55044-10b1-426f-9247-bb680e5fe0c8", uuid.to_string());
+
+        assert!(Uuid::from_str("").is_err());
+        assert!(Uuid::from_str("67e5504410b1426f9247bb680e5fe0c8").is_err());
+        assert!(Uuid::from_str("67e55044-10b1-426f-9247-bb680e5fe0c").is_err());
+        assert!(Uuid::from_str("67e55044-10b1-426f-9247-bb680e5fe0cg").is_err());
+        assert!(Uuid::from_str("+7e55044-10b1-426f-9247-bb680e5fe0c8").is_err());
+    }
+
+    #[test]
+    fn date() {
+        assert_eq!(Ok(Date { year: 2018, month: 2, day: 28 }), Date::from_str("2018-02-28"));
+        assert_eq!(Ok(Date { year: 2000, month: 2, day: 29 }), Date::from_str("2000-02-29"));
+        assert_eq!("0999-12-31", Date::from_str("0999-12-31").unwrap().to_string());
+
+        assert!(Date::from_str("1900-02-29").is_err());
+        assert!(Date::from_str("2018-00-10").is_err());
+        assert!(Date::from_str("2018-13-10").is_err());
+        assert!(Date::from_str("2018-04-31").is_err());
+        assert!(Date::from_str("2018-04-00").is_err());
+        assert!(Date::from_str("2018-4-01").is_err());
+        assert!(Date::from_str("2018-+4-01").is_err());
+        assert!(Date::from_str("2018-04-01-").is_err());
+    }
+
+    #[test]
+    fn segments() {
+        let segments = Segments::parse("a/b%20c//./d%3Fe/", false).unwrap();
+        assert_eq!(&["a", "b c", "d?e"], &*segments);
+        assert_eq!("a/b%20c/d%3Fe", segments.to_string());
+        assert_eq!(Some(segments.clone()), Segments::parse(&segments.to_string(), false));
+        assert_eq!("a/b%20c/d%3Fe", String::from(segments));
+
+        assert_eq!(Some(Segments::default()), Segments::parse("", false));
+        assert_eq!(None, Segments::parse("a/../b", false));
+        assert_eq!(None, Segments::parse("a/%2e%2E/b", false));
+        assert_eq!(None, Segments::parse("a/%ff", false));
+        assert_eq!(None, Segments::parse("a/d%2Fe", false));
+        assert_eq!(None, Segments::parse("%2e%2e%2f%2e%2e%2fetc/passwd", false));
+        assert_eq!(None, Segments::parse("..%5c..%5cwin.ini", false));
+        assert_eq!(None, Segments::parse("a%00.txt", false));
+        assert_eq!(None, Segments::parse("%2e%2e%2fetc", true));
+        assert_eq!(Some(Segments::from(vec!["a", "..", "b"])), Segments::parse("a/../b", true));
+    }
+
+    #[test]
+    fn converters() {
+        let mut converters = Converters::with_default_converters();
+        converters.insert("upper", |value| Some(Box::new(value.to_uppercase()) as TypedValue));
+
+        let number = converters.get(Type::NUMBER_NAME).unwrap();
+        assert_eq!(Some(&42u64), number("42").unwrap().downcast_ref());
+        assert!(number("18446744073709551616").is_none());
+
+        let int = converters.get(Type::INT_NAME).unwrap();
+        assert_eq!(Some(&-42i64), int("-42").unwrap().downcast_ref());
+        assert!(int("9223372036854775808").is_none());
+
+        let upper = converters.get("upper").unwrap();
+        assert_eq!(Some(&"ABC".to_string()), upper("abc").unwrap().downcast_ref());
+        assert!(converters.get(Type::STRING_NAME).is_none());
+
+        assert!(converters.get_for(Type::DATE_NAME, Type::DATE_PATTERN).is_some());
+        assert!(converters.get_for(Type::DATE_NAME, "[0-9]{8}").is_none());
+        assert!(converters.get_for("upper", "[a-z]+").is_some());
+        converters.insert_from_str::<u32>(Type::DATE_NAME);
+        assert!(converters.get_for(Type::DATE_NAME, "[0-9]{8}").is_some());
+    }
+}
@@ -0,0 +1,113 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+/// The kind of a glob syntax problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobErrorKind {
+    /// A `{` without the closing `}`.
+    UnclosedParam,
+    /// A `}` without the opening `{`, it should be escaped as `\}` to be matched literally.
+    UnmatchedClose,
+    /// A `[` without the closing `]`.
+    UnclosedOptional,
+    /// A `]` without the opening `[`, it should be escaped as `\]` to be matched literally.
+    UnmatchedOptionalClose,
+    /// A param without a name and a type, like `{}`.
+    EmptyParam,
+    /// A param with `:` but without a type after it, like `{id:}`.
+    EmptyType,
+    /// A tail param with a type, like `{*path:.*}`, it always matches the rest of the path.
+    TypedTail,
+    /// A param name which isn't an identifier, like `{user-id}`.
+    InvalidName(String),
+    /// A param name used more than once in the glob.
+    DuplicateName(String),
+    /// A default value for a param which isn't in the glob.
+    UnknownDefault(String),
+    /// A validator for a param which isn't in the glob.
+    UnknownValidator(String),
+    /// A param type (named or inline) which isn't a valid regex, with the regex error message.
+    InvalidPattern(String),
+    /// A glob type referring to itself through the other types, with the chain of the types.
+    TypeCycle(Vec<String>),
+    /// The glob isn't valid UTF-8.
+    InvalidUtf8,
+    /// The whole glob regex is invalid, which may happen with the raw regex syntax.
+    InvalidRegex(String),
+}
+
+impl fmt::Display for GlobErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GlobErrorKind::UnclosedParam => f.write_str("unclosed param, expected `}`"),
+            GlobErrorKind::UnmatchedClose => f.write_str("unmatched `}`, escape it as `\\}` to match it literally"),
+            GlobErrorKind::UnclosedOptional => f.write_str("unclosed optional section, expected `]`"),
+            GlobErrorKind::UnmatchedOptionalClose => f.write_str("unmatched `]`, escape it as `\\]` to match it literally"),
+            GlobErrorKind::EmptyParam => f.write_str("empty param"),
+            GlobErrorKind::EmptyType => f.write_str("empty param type after `:`"),
+            GlobErrorKind::TypedTail => f.write_str("a tail param can't have a type"),
+            GlobErrorKind::InvalidName(ref name) => write!(f, "invalid param name `{}`, expected an identifier", name),
+            GlobErrorKind::DuplicateName(ref name) => write!(f, "duplicate param name `{}`", name),
+            GlobErrorKind::UnknownDefault(ref name) => write!(f, "default value for the unknown param `{}`", name),
+            GlobErrorKind::UnknownValidator(ref name) => write!(f, "validator for the unknown param `{}`", name),
+            GlobErrorKind::InvalidPattern(ref message) => write!(f, "invalid param type regex: {}", message),
+            GlobErrorKind::TypeCycle(ref types) => write!(f, "cyclic glob types `{}`", types.join(" -> ")),
+            GlobErrorKind::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            GlobErrorKind::InvalidRegex(ref message) => write!(f, "invalid glob regex: {}", message),
+        }
+    }
+}
+
+/// A glob syntax error with the byte range of the problem in the original glob.
+///
+/// It is displayed with the glob and carets under the problem:
+///
+/// ```text
+/// error: invalid param name `user-id`, expected an identifier
+///   |
+///   | /users/{user-id}
+///   |         ^^^^^^^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobError {
+    pub kind: GlobErrorKind,
+    /// The byte range of the problem in the glob.
+    pub span: Range<usize>,
+    glob: Vec<u8>,
+}
+
+impl GlobError {
+    pub fn new(kind: GlobErrorKind, glob: &[u8], span: Range<usize>) -> Self {
+        GlobError {
+            kind,
+            span,
+            glob: glob.to_vec(),
+        }
+    }
+
+    /// The glob text, with the invalid UTF-8 sequences replaced.
+    pub fn glob(&self) -> String {
+        String::from_utf8_lossy(&self.glob).into_owned()
+    }
+
+    /// The glob and the carets under the problem, without the message.
+    pub fn render_span(&self) -> String {
+        let start = self.span.start.min(self.glob.len());
+        let end = self.span.end.min(self.glob.len()).max(start);
+        let offset = String::from_utf8_lossy(&self.glob[..start]).chars().count();
+        let width = String::from_utf8_lossy(&self.glob[start..end]).chars().count().max(1);
+
+        format!("  |\n  | {}\n  | {}{}", self.glob(), " ".repeat(offset), "^".repeat(width))
+    }
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error: {}\n{}", self.kind, self.render_span())
+    }
+}
+
+impl Error for GlobError {
+    fn description(&self) -> &str { "Invalid glob" }
+}
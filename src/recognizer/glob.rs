@@ -1,4 +1,10 @@
-use recognizer::types::{GlobTypes, DefaultStore};
+use std::sync::Arc;
+
+use regex::Regex;
+
+use recognizer::{Recognizer, ParamChunk, GlobError};
+use recognizer::types::{GlobTypes, DefaultStore, DefaultStoreBuild};
+use recognizer::validators::{Validator, ValidationFailure};
 
 #[derive(Default)]
 pub struct Glob<S, T = DefaultStore>
@@ -7,6 +13,11 @@ pub struct Glob<S, T = DefaultStore>
 {
     path: S,
     types: Option<T>,
+    raw_regex: bool,
+    defaults: Vec<(String, String)>,
+    allow_parent_segments: bool,
+    validators: Vec<(String, Validator)>,
+    validation_failure: ValidationFailure,
 }
 
 impl<S, T> Glob<S, T>
@@ -17,6 +28,110 @@ impl<S, T> Glob<S, T>
         Glob {
             path,
             types,
+            raw_regex: false,
+            defaults: Vec::new(),
+            allow_parent_segments: false,
+            validators: Vec::new(),
+            validation_failure: ValidationFailure::NoMatch,
+        }
+    }
+
+    /// Treat the text outside of `{...}` params as a regex instead of matching it literally,
+    /// as it was done by the previous versions.
+    ///
+    /// ```ignore
+    /// router.get(Glob::from("/files/.*\\.(png|jpg)").raw_regex(), handler, None);
+    /// ```
+    pub fn raw_regex(mut self) -> Self {
+        self.raw_regex = true;
+        self
+    }
+
+    /// Replace the types of the glob, keeping its path and options.
+    pub fn with_types<U: GlobTypes>(self, types: Option<U>) -> Glob<S, U> {
+        Glob {
+            path: self.path,
+            types,
+            raw_regex: self.raw_regex,
+            defaults: self.defaults,
+            allow_parent_segments: self.allow_parent_segments,
+            validators: self.validators,
+            validation_failure: self.validation_failure,
+        }
+    }
+
+    pub fn is_raw_regex(&self) -> bool {
+        self.raw_regex
+    }
+
+    /// Set the value of the param `name` for the paths without it, the param is absent
+    /// in `Params` otherwise.
+    ///
+    /// ```ignore
+    /// router.get(Glob::from("/list[/page/{page:number}]").with_default("page", "1"), handler, None);
+    /// ```
+    pub fn with_default<N, V>(mut self, name: N, value: V) -> Self
+        where N: Into<String>,
+              V: Into<String>
+    {
+        let name = name.into();
+        self.defaults.retain(|(other, _)| *other != name);
+        self.defaults.push((name, value.into()));
+        self
+    }
+
+    pub fn defaults(&self) -> &[(String, String)] {
+        &self.defaults
+    }
+
+    /// Match the `{*name}` tail params with `..` segments, the route doesn't match them
+    /// by default to prevent a path traversal.
+    pub fn allow_parent_segments(mut self) -> Self {
+        self.allow_parent_segments = true;
+        self
+    }
+
+    pub fn is_parent_segments_allowed(&self) -> bool {
+        self.allow_parent_segments
+    }
+
+    /// Check the param `name` with the `validator` after the glob regex matches, in addition
+    /// to the validators of its glob type.
+    ///
+    /// ```ignore
+    /// let glob = Glob::from("/items/{id:number}")
+    ///     .with_validator("id", |id| id.parse::<u32>().map_or(false, |id| id >= 1 && id <= 10000));
+    /// ```
+    pub fn with_validator<N, F>(mut self, name: N, validator: F) -> Self
+        where N: Into<String>,
+              F: Fn(&str) -> bool + Send + Sync + 'static
+    {
+        self.validators.push((name.into(), Arc::new(validator)));
+        self
+    }
+
+    pub fn validators(&self) -> &[(String, Validator)] {
+        &self.validators
+    }
+
+    /// Set what the route does with a param rejected by a validator, it doesn't match by default.
+    pub fn on_validation_failure(mut self, failure: ValidationFailure) -> Self {
+        self.validation_failure = failure;
+        self
+    }
+
+    pub fn validation_failure(&self) -> ValidationFailure {
+        self.validation_failure
+    }
+
+    /// Parse the glob with its types (or the default ones) and syntax.
+    pub fn parse(&self) -> Result<(Regex, Vec<ParamChunk>), GlobError> {
+        let types_default = DefaultStore::with_default_types();
+        match (self.types(), self.raw_regex) {
+            (Some(types), false) => Recognizer::parse_glob(self.path(), types.store()),
+            (Some(types), true) => Recognizer::parse_raw_glob(self.path(), types.store()),
+            (None, false) => Recognizer::parse_glob(self.path(), &types_default),
+            (None, true) => Recognizer::parse_raw_glob(self.path(), &types_default),
         }
     }
 
@@ -106,5 +221,23 @@ mod tests {
         types.insert("key", "value".to_string());
         assert_glob_key(("", &types), "key", "value".to_string());
         assert_glob_key(("", types), "key", "value".to_string());
+
+        assert!(!Glob::from(glob_str).is_raw_regex());
+        assert!(Glob::from(glob_str).raw_regex().is_raw_regex());
+        assert!(Glob::from((glob_str, &types_default)).raw_regex().is_raw_regex());
+
+        let glob = Glob::from(glob_str).with_default("page", "1").with_default("page", "2");
+        assert_eq!(&[("page".to_string(), "2".to_string())], glob.defaults());
+
+        let glob = Glob::from(glob_str)
+            .with_validator("id", |id| id != "0")
+            .on_validation_failure(ValidationFailure::BadRequest);
+        assert_eq!(1, glob.validators().len());
+        assert!(!(glob.validators()[0].1)("0"));
+        assert_eq!(ValidationFailure::BadRequest, glob.validation_failure());
+        assert_eq!(ValidationFailure::NoMatch, Glob::from(glob_str).validation_failure());
+
+        assert!(!Glob::from(glob_str).is_parent_segments_allowed());
+        assert!(Glob::from(glob_str).allow_parent_segments().is_parent_segments_allowed());
     }
 }
\ No newline at end of file
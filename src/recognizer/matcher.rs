@@ -1,18 +1,27 @@
-use std::collections::BTreeMap;
 use ferrum::Handler;
 
-pub type Params = BTreeMap<String, String>;
+use recognizer::converters::TypedParams;
+use recognizer::params::{ParamError, Params};
+use recognizer::Recognizer;
 
 pub struct RouteMatch<'a> {
-    pub handler: &'a Box<Handler>,
-    pub params: Params
+    pub handler: &'a dyn Handler,
+    pub params: Params,
+    pub typed_params: TypedParams,
+    /// The param rejected by a validator of a route answering with a BadRequest.
+    pub invalid_param: Option<ParamError>,
+    /// The recognizer of the matched route.
+    pub recognizer: Option<&'a Recognizer>,
 }
 
 impl<'a> RouteMatch<'a> {
-    pub fn new(handler: &'a Box<Handler>, params: Params) -> RouteMatch {
+    pub fn new(handler: &'a dyn Handler, params: Params) -> RouteMatch<'a> {
         RouteMatch {
             handler,
-            params
+            params,
+            typed_params: TypedParams::new(),
+            invalid_param: None,
+            recognizer: None,
         }
     }
 }
\ No newline at end of file
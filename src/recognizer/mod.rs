@@ -1,17 +1,33 @@
 use std::error::Error;
 use std::convert::AsRef;
+use std::sync::Arc;
 
 use ferrum::Handler;
 use regex::Regex;
 
+use router::MatchedRoute;
+
 pub mod types;
 pub mod glob;
 pub mod matcher;
+pub mod params;
+pub mod converters;
+pub mod validators;
+pub mod error;
+pub mod ast;
 pub use self::types::*;
 pub use self::glob::*;
 pub use self::matcher::*;
+pub use self::params::*;
+pub use self::converters::*;
+pub use self::validators::*;
+pub use self::error::*;
+pub use self::ast::*;
+
+pub type RecognizerResult<T = Recognizer> = Result<T, Box<dyn Error>>;
 
-pub type RecognizerResult<T = Recognizer> = Result<T, Box<Error>>;
+/// The glob AST, the glob regex with its params and the param chunks.
+type ParsedGlob = (GlobAst, Regex, GlobRegex, Vec<ParamChunk>);
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParamChunk {
@@ -21,9 +37,32 @@ pub struct ParamChunk {
 }
 
 pub struct Recognizer {
+    pub glob: GlobAst,
     pub glob_regex: Regex,
     pub param_chunks: Vec<ParamChunk>,
-    pub handler: Box<Handler>,
+    pub param_converters: Vec<(String, Converter)>,
+    /// The params converted by a default converter and their glob types, a value rejected by
+    /// the converter is an invalid param, see `Converters::is_default_for`.
+    pub default_converted_params: Vec<(String, String)>,
+    /// The regex group names of the captures nested in the composed types and their param names.
+    pub nested_params: Vec<(String, String)>,
+    /// The regex source of each top-level node of the glob, see `GlobRegex::node_patterns`.
+    pub node_patterns: Vec<String>,
+    /// The regex group names of all params and their param names, in the glob order.
+    pub param_groups: Vec<(String, Arc<str>)>,
+    /// The validators of the params by their glob types and the route ones.
+    pub param_validators: Vec<(String, Validator)>,
+    /// What the route does with a param rejected by a validator.
+    pub validation_failure: ValidationFailure,
+    /// The values of the params absent in the path, see `Glob::with_default`.
+    pub param_defaults: Vec<(String, String)>,
+    /// The names of the `{*name}` tail params, converted into `Segments` on matching.
+    pub tail_params: Vec<String>,
+    /// Match the tail params with `..` segments, see `Glob::allow_parent_segments`.
+    pub allow_parent_segments: bool,
+    /// The route the recognizer is registered for in a `Router`.
+    pub route: Option<Arc<MatchedRoute>>,
+    pub handler: Box<dyn Handler>,
 }
 
 pub trait Recognize {
@@ -31,114 +70,253 @@ pub trait Recognize {
 }
 
 impl Recognizer {
-    pub fn new<G, N, P>(glob: G, handler: Box<Handler>, types: Option<&Store<N, P>>) -> RecognizerResult
+    pub fn new<G, N, P>(glob: G, handler: Box<dyn Handler>, types: Option<&Store<N, P>>) -> RecognizerResult
+        where G: AsRef<[u8]>,
+              N: TypeName,
+              P: TypePattern
+    {
+        Recognizer::with_converters(glob, handler, types, None)
+    }
+
+    /// Like `new`, but the params which glob types have a converter in `converters` (or in the
+    /// default converters) are converted on matching, and the route doesn't match if any of
+    /// the conversions fails. A failed default conversion results in a `RouteMatch` with an
+    /// invalid param instead, see `Converters::is_default_for`.
+    pub fn with_converters<G, N, P>(
+        glob: G,
+        handler: Box<dyn Handler>,
+        types: Option<&Store<N, P>>,
+        converters: Option<&Converters>
+    ) -> RecognizerResult
         where G: AsRef<[u8]>,
               N: TypeName,
               P: TypePattern
+    {
+        Recognizer::build(glob.as_ref(), handler, types, converters, None, false)
+    }
+
+    /// Construct a recognizer for the `glob` with its types, converters, validators, syntax
+    /// and options.
+    pub fn from_glob<S, T>(glob: &Glob<S, T>, handler: Box<dyn Handler>) -> RecognizerResult
+        where S: AsRef<[u8]>,
+              T: GlobTypes
+    {
+        let types = glob.types().map(|types| types.store());
+        let converters = glob.types().and_then(|types| types.converters());
+        let validators = glob.types().and_then(|types| types.validators());
+        let mut recognizer = Recognizer::build(glob.path(), handler, types, converters, validators, glob.is_raw_regex())?;
+
+        let param_names = recognizer.glob.param_names();
+        let unknown_error = |kind| GlobError::new(kind, glob.path(), 0..glob.path().len());
+        if let Some((name, _)) = glob.defaults().iter().find(|(name, _)| !param_names.contains(&name.as_str())) {
+            return Err(unknown_error(GlobErrorKind::UnknownDefault(name.clone())).into());
+        }
+        if let Some((name, _)) = glob.validators().iter().find(|(name, _)| !param_names.contains(&name.as_str())) {
+            return Err(unknown_error(GlobErrorKind::UnknownValidator(name.clone())).into());
+        }
+
+        recognizer.param_defaults = glob.defaults().to_vec();
+        recognizer.param_validators.extend(glob.validators().iter().cloned());
+        recognizer.validation_failure = glob.validation_failure();
+        recognizer.allow_parent_segments = glob.is_parent_segments_allowed();
+        Ok(recognizer)
+    }
+
+    fn build<N, P>(
+        glob: &[u8],
+        handler: Box<dyn Handler>,
+        types: Option<&Store<N, P>>,
+        converters: Option<&Converters>,
+        validators: Option<&Validators>,
+        raw_regex: bool
+    ) -> RecognizerResult
+        where N: TypeName,
+              P: TypePattern
     {
         let types_default = DefaultStore::with_default_types();
-        let (glob_regex, param_chunks) = match types {
-            Some(types) => Recognizer::parse_glob(glob, types),
-            None => Recognizer::parse_glob(glob, &types_default)
+        let (ast, regex, glob_regex, param_chunks) = match types {
+            Some(types) => Recognizer::parse(glob, types, raw_regex),
+            None => Recognizer::parse(glob, &types_default, raw_regex)
         }?;
 
+        let mut all_converters = Converters::with_default_converters();
+        if let Some(converters) = converters {
+            all_converters.extend(converters);
+        }
+        let type_pattern = |type_name: &str| match types {
+            Some(types) => types.get(type_name).map(|pattern| pattern.as_ref().to_string()),
+            None => types_default.get(type_name).map(|pattern| pattern.to_string()),
+        };
+        let param_converters = glob_regex.param_types.iter()
+            .filter_map(|(name, type_name)| {
+                let pattern = type_pattern(type_name)?;
+                all_converters.get_for(type_name, &pattern).map(|converter| (name.clone(), converter.clone()))
+            })
+            .collect();
+        let default_converted_params = glob_regex.param_types.iter()
+            .filter(|(_, type_name)| match type_pattern(type_name) {
+                Some(pattern) => all_converters.is_default_for(type_name, &pattern),
+                None => false,
+            })
+            .cloned()
+            .collect();
+        let param_validators = glob_regex.param_types.iter()
+            .filter_map(|(name, type_name)| {
+                validators.and_then(|validators| validators.get(type_name))
+                    .map(|validator| (name.clone(), validator.clone()))
+            })
+            .collect();
+
+        let mut tail_params = Vec::new();
+        for_each_param(&ast.nodes, &mut |node| if let GlobNode::Tail(ref tail) = *node {
+            tail_params.push(tail.name.clone());
+        });
+
+        let mut param_groups: Vec<_> = param_chunks.iter()
+            .map(|chunk| (chunk.name.clone(), chunk.name.clone()))
+            .chain(glob_regex.nested_params.iter().cloned())
+            .map(|(group, name)| (group, Arc::from(name)))
+            .collect();
+        param_groups.sort_by_key(|(group, _)| {
+            glob_regex.pattern.find(&format!("(?P<{}>", group)).unwrap_or(usize::MAX)
+        });
+
         Ok(Recognizer {
-            glob_regex,
+            glob: ast,
+            glob_regex: regex,
             param_chunks,
+            param_converters,
+            default_converted_params,
+            nested_params: glob_regex.nested_params,
+            node_patterns: glob_regex.node_patterns,
+            param_groups,
+            param_validators,
+            validation_failure: ValidationFailure::NoMatch,
+            param_defaults: Vec::new(),
+            tail_params,
+            allow_parent_segments: false,
+            route: None,
             handler,
         })
     }
 
-    pub fn parse_glob<G, N, P>(glob: G, types: &Store<N, P>) -> RecognizerResult<(Regex, Vec<ParamChunk>)>
+    /// Parse the glob into a regex matching the whole path and the param chunks.
+    ///
+    /// The text outside of `{...}` params is matched literally, `\{` and `\}` (or a backslash
+    /// before any other character) match the escaped character itself. A `[...]` section may be
+    /// absent in the path and a `{*name}` param captures the rest of it.
+    ///
+    /// The glob is parsed into a `GlobAst` first, see `GlobAst::parse`. A syntax error is
+    /// reported as a `GlobError` with its byte range in the glob.
+    pub fn parse_glob<G, N, P>(glob: G, types: &Store<N, P>) -> Result<(Regex, Vec<ParamChunk>), GlobError>
         where G: AsRef<[u8]>,
               N: TypeName,
               P: TypePattern
     {
-        let mut param_chunks = Vec::<ParamChunk>::new();
-        let mut pattern = "^".as_bytes().to_vec();
-
-        let identifier_regex = Regex::new("^[_a-zA-Z][_0-9a-zA-Z]*$").unwrap();
-
-        let mut iter = glob.as_ref().iter().enumerate();
-        while let Some((index, &bch)) = iter.next() {
-            match bch {
-                b'{' if index == 0 || glob.as_ref()[index - 1] != b'\\' => {
-                    let mut param_name = Vec::new();
-                    let mut param_type = Vec::new();
-                    let mut is_type = false;
-                    let start = index;
-
-                    while let Some((index, &bch)) = iter.next() {
-                        match bch {
-                            b' ' | b'\t' | b'\r' | b'\n' => continue,
-                            b':' if !is_type => is_type = true,
-                            b'}' if index == 0 || glob.as_ref()[index - 1] != b'\\' => {
-                                let end = index + 1;
-
-                                if param_name.len() > 0 || param_type.len() > 0 {
-                                    let param_name = String::from_utf8(param_name)?;
-
-                                    let regex_chunk = if param_name.len() > 0 && !identifier_regex.is_match(param_name.as_str()) {
-                                        "{".to_string() + param_name.as_str() + "}"
-                                    } else {
-                                        let prefix = if param_name.len() > 0 {
-                                            let prefix = format!("(?P<{}>", param_name);
-                                            param_chunks.push(ParamChunk {
-                                                name: param_name.clone(),
-                                                start,
-                                                end
-                                            });
-                                            prefix
-                                        } else {
-                                            "(".to_string()
-                                        };
-
-                                        let param_type = String::from_utf8(param_type)?;
-
-                                        let regex_type = if param_type.len() > 0 {
-                                            if let Some(regex_pattern) = types.get(param_type.as_str()) {
-                                                regex_pattern.as_ref()
-                                            } else {
-                                                param_type.as_str()
-                                            }
-                                        } else {
-                                            if let Some(regex_pattern) = types.get(param_name.as_str()) {
-                                                regex_pattern.as_ref()
-                                            } else {
-                                                Type::STRING_PATTERN
-                                            }
-                                        };
-
-                                        prefix + regex_type + ")"
-                                    };
-                                    pattern.extend(regex_chunk.as_bytes().iter());
-                                }
-                                break;
-                            },
-                            _ if is_type => param_type.push(bch),
-                            _ => param_name.push(bch),
-                        }
-                    }
-                },
-                _ => pattern.push(bch),
-            }
-        }
-        let mut pattern = String::from_utf8(pattern)?;
-        pattern += if pattern.chars().rev().next().unwrap_or('_') == '/' { "$" } else { "/?$" };
-        Ok((Regex::new(&pattern)?, param_chunks))
+        Recognizer::parse(glob.as_ref(), types, false)
+            .map(|(_, glob_regex, _, param_chunks)| (glob_regex, param_chunks))
+    }
+
+    /// Like `parse_glob`, but the text outside of `{...}` params is copied into the regex as is,
+    /// so it can contain regex syntax.
+    pub fn parse_raw_glob<G, N, P>(glob: G, types: &Store<N, P>) -> Result<(Regex, Vec<ParamChunk>), GlobError>
+        where G: AsRef<[u8]>,
+              N: TypeName,
+              P: TypePattern
+    {
+        Recognizer::parse(glob.as_ref(), types, true)
+            .map(|(_, glob_regex, _, param_chunks)| (glob_regex, param_chunks))
+    }
+
+    fn parse<N, P>(glob: &[u8], types: &Store<N, P>, raw_regex: bool) -> Result<ParsedGlob, GlobError>
+        where N: TypeName,
+              P: TypePattern
+    {
+        let ast = GlobAst::parse(glob, types, raw_regex)?;
+        let glob_regex = ast.regex(types)?;
+        let regex = Regex::new(&glob_regex.pattern).map_err(|regex_error| {
+            GlobError::new(GlobErrorKind::InvalidRegex(regex_error.to_string()), glob, 0..glob.len())
+        })?;
+
+        let mut param_chunks = Vec::new();
+        for_each_param(&ast.nodes, &mut |node| {
+            let (name, span) = match *node {
+                GlobNode::Param(ParamNode { name: Some(ref name), ref span, .. }) => (name, span),
+                GlobNode::Tail(TailNode { ref name, ref span }) => (name, span),
+                _ => return,
+            };
+            param_chunks.push(ParamChunk {
+                name: name.clone(),
+                start: span.start,
+                end: span.end,
+            });
+        });
+        Ok((ast, regex, glob_regex, param_chunks))
+    }
+
+    /// A match of the route with the invalid param `name`, its handler isn't called.
+    fn invalid_match(&self, params: Params, name: &str, value: String, reason: String) -> RouteMatch<'_> {
+        let mut route_match = RouteMatch::new(&*self.handler, params);
+        route_match.recognizer = Some(self);
+        route_match.invalid_param = Some(ParamError::Invalid { name: name.to_string(), value, reason });
+        route_match
     }
 }
 
 impl Recognize for Recognizer {
     fn recognize<'a>(&'a self, path: &str) -> Option<RouteMatch<'a>> {
         if let Some(captures) = self.glob_regex.captures(path) {
-            let mut params = Params::new();
-            for &ParamChunk { ref name, .. } in self.param_chunks.iter() {
-                if let Some(param_match) = captures.name(name) {
-                    params.insert(name.clone(), param_match.as_str().to_string());
+            let mut params = Params::with_path(path);
+            for (group, name) in self.param_groups.iter() {
+                if let Some(param_match) = captures.name(group) {
+                    params.push_span(name.clone(), param_match.start()..param_match.end());
+                } else if let Some((_, value)) = self.param_defaults.iter().find(|(default, _)| **default == **name) {
+                    params.push_owned(name.clone(), value.clone());
+                }
+            }
+
+            for (name, validator) in self.param_validators.iter() {
+                let value = match params.get(name) {
+                    Some(value) if !validator(value) => value.to_string(),
+                    _ => continue,
+                };
+                match self.validation_failure {
+                    ValidationFailure::NoMatch => return None,
+                    ValidationFailure::BadRequest => {
+                        let reason = "rejected by the validator".to_string();
+                        return Some(self.invalid_match(params, name, value, reason));
+                    },
                 }
             }
-            Some(RouteMatch::new(&self.handler, params))
+
+            let mut typed_params = TypedParams::new();
+            for (name, converter) in self.param_converters.iter() {
+                let value = match params.get(name) {
+                    Some(value) => value,
+                    None => continue,
+                };
+                match converter(value) {
+                    Some(typed_value) => typed_params.insert(name.clone(), typed_value),
+                    None => {
+                        let (_, type_name) = self.default_converted_params.iter()
+                            .find(|(default, _)| default == name)?;
+                        let value = value.to_string();
+                        let reason = format!("not a valid `{}`", type_name);
+                        return Some(self.invalid_match(params, name, value, reason));
+                    },
+                }
+            }
+            for name in self.tail_params.iter() {
+                if let Some(value) = params.get_raw(name) {
+                    let segments = Segments::parse(value, self.allow_parent_segments)?;
+                    typed_params.insert(name.clone(), Box::new(segments));
+                }
+            }
+
+            let mut route_match = RouteMatch::new(&*self.handler, params);
+            route_match.typed_params = typed_params;
+            route_match.recognizer = Some(self);
+            Some(route_match)
         } else {
             None
         }
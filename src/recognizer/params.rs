@@ -0,0 +1,453 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Index, Range};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::vec;
+
+use ferrum::{Response, FerrumError, StatusCode, mime};
+use url::percent_encoding::percent_decode;
+
+/// The params of a matched route, in the order of the glob.
+///
+/// Each param keeps its value percent-decoded (`get`) and as captured in the path
+/// (`get_raw`). The captured values are ranges of the matched path, so a match copies
+/// the path once instead of allocating a string per param. A name may be repeated with
+/// `append`, e.g. for the query params of `uri_for`, and `to_map` gives a `BTreeMap` view
+/// of the first decoded values for the code expecting one.
+///
+/// ```ignore
+/// // "/posts/{year}/{slug}" matched by "/posts/2018/hello%20world"
+/// assert_eq!(Some("hello world"), params.get("slug"));
+/// assert_eq!(Some("hello%20world"), params.get_raw("slug"));
+/// assert_eq!(Some(("year", "2018")), params.get_index(0).map(|param| (param.name, param.raw)));
+/// ```
+#[derive(Clone, Default)]
+pub struct Params {
+    path: String,
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone)]
+struct Entry {
+    name: Arc<str>,
+    raw: Value,
+    /// The percent-decoded value when it differs from the raw one.
+    decoded: Option<String>,
+}
+
+#[derive(Clone)]
+enum Value {
+    Span(Range<usize>),
+    Owned(String),
+}
+
+/// A param borrowed from `Params`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param<'a> {
+    pub name: &'a str,
+    /// The value as captured in the path.
+    pub raw: &'a str,
+    /// The percent-decoded value, the raw one if it isn't valid UTF-8 once decoded.
+    pub decoded: &'a str,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    /// Params which values are captured from `path`, see `push_span`.
+    pub(crate) fn with_path(path: &str) -> Self {
+        Params {
+            path: path.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Append the param `name` captured at `span` of the path.
+    pub(crate) fn push_span(&mut self, name: Arc<str>, span: Range<usize>) {
+        let decoded = decode(&self.path[span.clone()]);
+        self.entries.push(Entry { name, raw: Value::Span(span), decoded });
+    }
+
+    pub(crate) fn push_owned(&mut self, name: Arc<str>, value: String) {
+        let decoded = decode(&value);
+        self.entries.push(Entry { name, raw: Value::Owned(value), decoded });
+    }
+
+    fn raw<'a>(&'a self, entry: &'a Entry) -> &'a str {
+        match entry.raw {
+            Value::Span(ref span) => &self.path[span.clone()],
+            Value::Owned(ref value) => value,
+        }
+    }
+
+    fn param<'a>(&'a self, entry: &'a Entry) -> Param<'a> {
+        let raw = self.raw(entry);
+        Param {
+            name: &entry.name,
+            raw,
+            decoded: entry.decoded.as_deref().unwrap_or(raw),
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| &*entry.name == name)
+    }
+
+    /// Set the param `name` to `value`, keeping its position, and remove its other values.
+    ///
+    /// Returns the previous raw value, like `BTreeMap::insert`.
+    pub fn insert(&mut self, name: String, value: String) -> Option<String> {
+        match self.position(&name) {
+            Some(index) => {
+                let previous = self.raw(&self.entries[index]).to_string();
+                let decoded = decode(&value);
+                self.entries[index].raw = Value::Owned(value);
+                self.entries[index].decoded = decoded;
+                let mut position = 0;
+                self.entries.retain(|entry| {
+                    position += 1;
+                    position <= index + 1 || *entry.name != *name
+                });
+                Some(previous)
+            },
+            None => {
+                self.push_owned(Arc::from(name), value);
+                None
+            }
+        }
+    }
+
+    /// Add another value of the param `name` after the existing params.
+    pub fn append(&mut self, name: String, value: String) {
+        self.push_owned(Arc::from(name), value);
+    }
+
+    /// The first percent-decoded value of the param `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.position(name).map(|index| self.param(&self.entries[index]).decoded)
+    }
+
+    /// The first raw value of the param `name`, as captured in the path.
+    pub fn get_raw(&self, name: &str) -> Option<&str> {
+        self.position(name).map(|index| self.raw(&self.entries[index]))
+    }
+
+    /// All percent-decoded values of the param `name` in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries.iter()
+            .filter(move |entry| &*entry.name == name)
+            .map(move |entry| self.param(entry).decoded)
+    }
+
+    /// The param at `index` in the glob order.
+    pub fn get_index(&self, index: usize) -> Option<Param<'_>> {
+        self.entries.get(index).map(|entry| self.param(entry))
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// Remove all values of the param `name`, returning the first raw one.
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let value = self.get_raw(name).map(str::to_string);
+        self.entries.retain(|entry| &*entry.name != name);
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The names and raw values in order.
+    pub fn iter(&self) -> ParamsIter<'_> {
+        ParamsIter { params: self, entries: self.entries.iter() }
+    }
+
+    /// The params with both of their values in order.
+    pub fn entries(&self) -> impl Iterator<Item = Param<'_>> {
+        self.entries.iter().map(move |entry| self.param(entry))
+    }
+
+    /// The first percent-decoded value of each param by name.
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+        for param in self.entries() {
+            map.entry(param.name.to_string()).or_insert_with(|| param.decoded.to_string());
+        }
+        map
+    }
+}
+
+fn decode(value: &str) -> Option<String> {
+    match percent_decode(value.as_bytes()).decode_utf8() {
+        Ok(Cow::Owned(decoded)) => Some(decoded),
+        _ => None,
+    }
+}
+
+/// An iterator over the names and raw values of `Params`.
+pub struct ParamsIter<'a> {
+    params: &'a Params,
+    entries: ::std::slice::Iter<'a, Entry>,
+}
+
+impl<'a> Iterator for ParamsIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let params = self.params;
+        self.entries.next().map(|entry| (&*entry.name, params.raw(entry)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a Params {
+    type Item = (&'a str, &'a str);
+    type IntoIter = ParamsIter<'a>;
+
+    fn into_iter(self) -> ParamsIter<'a> {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Params {
+    type Item = (String, String);
+    type IntoIter = vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<N, V> Extend<(N, V)> for Params
+    where N: Into<String>,
+          V: Into<String>
+{
+    fn extend<I: IntoIterator<Item = (N, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.append(name.into(), value.into());
+        }
+    }
+}
+
+impl<N, V> FromIterator<(N, V)> for Params
+    where N: Into<String>,
+          V: Into<String>
+{
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut params = Params::new();
+        params.extend(iter);
+        params
+    }
+}
+
+impl Index<&str> for Params {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &str {
+        self.get(name).expect("no param found for the name")
+    }
+}
+
+impl PartialEq for Params {
+    fn eq(&self, other: &Params) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Params {}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Typed access to the route parameters.
+///
+/// ```ignore
+/// use ferrum_router::ParseParam;
+///
+/// fn handler(request: &mut Request) -> FerrumResult<Response> {
+///     let id = request.extensions.get::<Router>().unwrap().get_parsed::<u64>("id")?;
+///     Ok(Response::new().with_content(format!("id: {}", id), mime::TEXT_PLAIN))
+/// }
+/// ```
+pub trait ParseParam {
+    /// Get the percent-decoded param `name` parsed as `T`.
+    ///
+    /// A missing or unparsable param results in a `ParamError`, which converts into
+    /// a `FerrumError` with a BadRequest response.
+    fn get_parsed<T>(&self, name: &str) -> Result<T, ParamError>
+        where T: FromStr,
+              T::Err: fmt::Display;
+
+    /// Like `get_parsed`, but a missing param results in `None`.
+    fn get_parsed_opt<T>(&self, name: &str) -> Result<Option<T>, ParamError>
+        where T: FromStr,
+              T::Err: fmt::Display;
+}
+
+impl ParseParam for Params {
+    fn get_parsed<T>(&self, name: &str) -> Result<T, ParamError>
+        where T: FromStr,
+              T::Err: fmt::Display
+    {
+        self.get_parsed_opt(name)?
+            .ok_or_else(|| ParamError::Missing(name.to_string()))
+    }
+
+    fn get_parsed_opt<T>(&self, name: &str) -> Result<Option<T>, ParamError>
+        where T: FromStr,
+              T::Err: fmt::Display
+    {
+        match self.get(name) {
+            Some(value) => T::from_str(value)
+                .map(Some)
+                .map_err(|error| ParamError::Invalid {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    reason: error.to_string(),
+                }),
+            None => Ok(None)
+        }
+    }
+}
+
+/// The error of a typed access to a route param,
+/// it is always accompanied by a BadRequest response when converted into a `FerrumError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// The param isn't present in the matched route.
+    Missing(String),
+    /// The param value can't be parsed into the requested type.
+    Invalid {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl ParamError {
+    /// The name of the param the error relates to.
+    pub fn name(&self) -> &str {
+        match *self {
+            ParamError::Missing(ref name) => name,
+            ParamError::Invalid { ref name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParamError::Missing(ref name) =>
+                write!(f, "Missing route param `{}`.", name),
+            ParamError::Invalid { ref name, ref value, ref reason } =>
+                write!(f, "Invalid route param `{}` = `{}`: {}.", name, value, reason),
+        }
+    }
+}
+
+impl Error for ParamError {
+    fn description(&self) -> &str { "Bad route param" }
+}
+
+impl From<ParamError> for FerrumError {
+    fn from(error: ParamError) -> FerrumError {
+        let response = Response::new()
+            .with_content(error.to_string(), mime::TEXT_PLAIN)
+            .with_status(StatusCode::BadRequest);
+        FerrumError::new(error, Some(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Params {
+        let mut params = Params::new();
+        params.insert("id".to_string(), "42".to_string());
+        params.insert("name".to_string(), "bob".to_string());
+        params
+    }
+
+    #[test]
+    fn ordered_params() {
+        let mut params = Params::with_path("/users/bob%20smith/42");
+        params.push_span(Arc::from("name"), 7..18);
+        params.push_span(Arc::from("id"), 19..21);
+        params.append("tag".to_string(), "a".to_string());
+        params.append("tag".to_string(), "b".to_string());
+
+        assert_eq!(vec![("name", "bob%20smith"), ("id", "42"), ("tag", "a"), ("tag", "b")], params.iter().collect::<Vec<_>>());
+        assert_eq!(Some("bob smith"), params.get("name"));
+        assert_eq!(Some("bob%20smith"), params.get_raw("name"));
+        assert_eq!("bob smith", &params["name"]);
+        assert_eq!("42", &params["id"]);
+        assert_eq!(Some(Param { name: "id", raw: "42", decoded: "42" }), params.get_index(1));
+        assert_eq!(None, params.get_index(4));
+        assert_eq!(vec!["a", "b"], params.get_all("tag").collect::<Vec<_>>());
+        assert_eq!(Ok("bob smith".to_string()), params.get_parsed::<String>("name"));
+
+        assert_eq!(Some("a".to_string()), params.insert("tag".to_string(), "c".to_string()));
+        assert_eq!(vec!["c"], params.get_all("tag").collect::<Vec<_>>());
+        assert_eq!(Some("bob%20smith".to_string()), params.insert("name".to_string(), "alice".to_string()));
+        assert_eq!(vec![("name", "alice"), ("id", "42"), ("tag", "c")], params.iter().collect::<Vec<_>>());
+
+        let map = params.to_map();
+        assert_eq!(vec!["id", "name", "tag"], map.keys().map(String::as_str).collect::<Vec<_>>());
+
+        assert_eq!(Some("42".to_string()), params.remove("id"));
+        assert!(!params.contains_key("id"));
+        assert_eq!(2, params.len());
+        assert_eq!(params, vec![("name", "alice"), ("tag", "c")].into_iter().collect());
+    }
+
+    #[test]
+    fn get_parsed() {
+        let params = params();
+
+        assert_eq!(Ok(42), params.get_parsed::<u32>("id"));
+        assert_eq!(Ok("bob".to_string()), params.get_parsed::<String>("name"));
+        assert_eq!(Ok(Some(42i64)), params.get_parsed_opt("id"));
+        assert_eq!(Ok(None), params.get_parsed_opt::<i64>("page"));
+
+        assert_eq!(Err(ParamError::Missing("page".to_string())), params.get_parsed::<u32>("page"));
+
+        let error = params.get_parsed::<u32>("name").unwrap_err();
+        assert_eq!("name", error.name());
+        assert_eq!(ParamError::Invalid {
+            name: "name".to_string(),
+            value: "bob".to_string(),
+            reason: "invalid digit found in string".to_string(),
+        }, error);
+    }
+
+    #[test]
+    fn into_ferrum_error() {
+        let error = FerrumError::from(params().get_parsed::<u8>("name").unwrap_err());
+
+        assert!(error.error.is::<ParamError>());
+        assert_eq!("Invalid route param `name` = `bob`: invalid digit found in string.", error.to_string());
+        assert_eq!(StatusCode::BadRequest, error.response.unwrap().status);
+    }
+}
@@ -84,20 +84,187 @@ fn parse_glob_single_param() {
     for glob in globs {
         let (regex, params) = Recognizer::parse_glob(glob, &types).unwrap();
 
-        assert!(!regex.is_match(""), glob);
-        assert!(!regex.is_match("test"), glob);
-        assert!(!regex.is_match("/"), glob);
-        assert!(regex.is_match("/posts/12"), glob);
-        assert!(regex.is_match("/posts/12/"), glob);
-        assert!(!regex.is_match("/posts/12a"), glob);
-        assert!(!regex.is_match("/posts/12/test"), glob);
-        assert!(!regex.is_match("/posts/new"), glob);
-        assert!(!regex.is_match("/posts/new/"), glob);
-        assert!(!regex.is_match("/posts/new/test"), glob);
+        assert!(!regex.is_match(""), "{}", glob);
+        assert!(!regex.is_match("test"), "{}", glob);
+        assert!(!regex.is_match("/"), "{}", glob);
+        assert!(regex.is_match("/posts/12"), "{}", glob);
+        assert!(regex.is_match("/posts/12/"), "{}", glob);
+        assert!(!regex.is_match("/posts/12a"), "{}", glob);
+        assert!(!regex.is_match("/posts/12/test"), "{}", glob);
+        assert!(!regex.is_match("/posts/new"), "{}", glob);
+        assert!(!regex.is_match("/posts/new/"), "{}", glob);
+        assert!(!regex.is_match("/posts/new/test"), "{}", glob);
         assert_eq!(params, vec![ParamChunk { name: "id".to_string(), start: 7, end: glob.len() }]);
     }
 }
 
+#[test]
+fn parse_glob_literal() {
+    let types = Store::<String, String>::default();
+
+    let (regex, params) = Recognizer::parse_glob("/feed.json", &types).unwrap();
+    assert_eq!(r"^/feed\.json/?$", regex.as_str());
+    assert!(regex.is_match("/feed.json"));
+    assert!(!regex.is_match("/feedXjson"));
+    assert_eq!(params, Vec::<ParamChunk>::new());
+
+    let (regex, _) = Recognizer::parse_glob(r"/c++/(a|b)*?/\[x\]^$", &types).unwrap();
+    assert!(regex.is_match("/c++/(a|b)*?/[x]^$"));
+    assert!(!regex.is_match("/cc/a/x"));
+
+    let (regex, params) = Recognizer::parse_glob(r"/set/\{{name}\}", &types).unwrap();
+    assert_eq!(format!(r"^/set/\{{(?P<name>{})\}}/?$", Type::STRING_PATTERN), regex.as_str());
+    assert!(regex.is_match("/set/{items}"));
+    assert!(!regex.is_match(r"/set/\{items\}"));
+    assert_eq!(params, vec![ParamChunk { name: "name".to_string(), start: 7, end: 13 }]);
+
+    let (regex, _) = Recognizer::parse_glob(r"/back\\slash/\{user-id\}", &types).unwrap();
+    assert!(regex.is_match(r"/back\slash/{user-id}"));
+    assert!(!regex.is_match("/backslash/{user-id}"));
+
+    let (regex, _) = Recognizer::parse_glob("/привет.txt", &types).unwrap();
+    assert!(regex.is_match("/привет.txt"));
+    assert!(!regex.is_match("/приветxtxt"));
+}
+
+#[test]
+fn parse_raw_glob() {
+    let types = Store::<String, String>::default();
+
+    let (regex, params) = Recognizer::parse_raw_glob("/feed.json", &types).unwrap();
+    assert_eq!("^/feed.json/?$", regex.as_str());
+    assert!(regex.is_match("/feedXjson"));
+    assert_eq!(params, Vec::<ParamChunk>::new());
+
+    let (regex, params) = Recognizer::parse_raw_glob(r"/files/.*\.(png|jpg)/\{{name}\}", &types).unwrap();
+    assert!(regex.is_match("/files/a/b.png/{x}"));
+    assert!(!regex.is_match("/files/a/b.gif/{x}"));
+    assert_eq!(params, vec![ParamChunk { name: "name".to_string(), start: 23, end: 29 }]);
+
+    assert!(Recognizer::parse_raw_glob("/a(b", &types).is_err());
+    assert!(Recognizer::parse_glob("/a(b", &types).is_ok());
+}
+
+#[test]
+fn parse_glob_errors() {
+    let types = DefaultStore::with_default_types();
+    let error = |glob: &str| Recognizer::parse_glob(glob, &types).unwrap_err();
+
+    let cases = vec![
+        ("/users/{id", GlobErrorKind::UnclosedParam, 7..10),
+        ("/users/id}", GlobErrorKind::UnmatchedClose, 9..10),
+        ("/users/{}", GlobErrorKind::EmptyParam, 7..9),
+        ("/users/{ : }", GlobErrorKind::EmptyParam, 7..12),
+        ("/users/{id:}", GlobErrorKind::EmptyType, 10..11),
+        ("/users/{user-id}", GlobErrorKind::InvalidName("user-id".to_string()), 8..15),
+        ("/users/{1st}", GlobErrorKind::InvalidName("1st".to_string()), 8..11),
+        ("/{id}/{ id }", GlobErrorKind::DuplicateName("id".to_string()), 8..10),
+    ];
+    for (glob, kind, span) in cases {
+        let error = error(glob);
+        assert_eq!(kind, error.kind, "{}", glob);
+        assert_eq!(span, error.span, "{}", glob);
+        assert_eq!(glob, error.glob());
+    }
+
+    let error = error("/posts/{id:[0-9}");
+    match error.kind {
+        GlobErrorKind::InvalidPattern(_) => assert_eq!(11..15, error.span),
+        kind => panic!("unexpected {:?}", kind),
+    }
+
+    let error = Recognizer::parse_raw_glob("/a(b", &types).unwrap_err();
+    match error.kind {
+        GlobErrorKind::InvalidRegex(_) => assert_eq!(0..4, error.span),
+        kind => panic!("unexpected {:?}", kind),
+    }
+
+    let error = Recognizer::parse_glob(&b"/a\xff/{id}"[..], &types).unwrap_err();
+    assert_eq!((GlobErrorKind::InvalidUtf8, 2..3), (error.kind, error.span));
+}
+
+#[test]
+fn parse_glob_nested_braces() {
+    let types = DefaultStore::with_default_types();
+
+    let (regex, params) = Recognizer::parse_glob("/{year:[0-9]{4}}/{slug}", &types).unwrap();
+    assert!(regex.is_match("/2018/hello"));
+    assert!(!regex.is_match("/18/hello"));
+    assert_eq!(params, vec![
+        ParamChunk { name: "year".to_string(), start: 1, end: 16 },
+        ParamChunk { name: "slug".to_string(), start: 17, end: 23 },
+    ]);
+}
+
+#[test]
+fn glob_error_display() {
+    let types = DefaultStore::with_default_types();
+
+    let error = Recognizer::parse_glob("/users/{user-id}", &types).unwrap_err();
+    assert_eq!(
+        "error: invalid param name `user-id`, expected an identifier\n  |\n  | /users/{user-id}\n  |         ^^^^^^^",
+        error.to_string()
+    );
+
+    let error = Recognizer::parse_glob("/привет/{id", &types).unwrap_err();
+    assert_eq!("  |\n  | /привет/{id\n  |         ^^^", error.render_span());
+}
+
+#[test]
+fn recognize_converted_params() {
+    let handler = || Box::new(|_: &mut ::ferrum::Request| Ok(::ferrum::Response::new()));
+
+    let recognizer = Recognizer::new(
+        "/posts/{id:number}/{uuid:uuid}/{date:date}/{name}",
+        handler(),
+        Option::<&DefaultStore>::None
+    ).unwrap();
+
+    let route_match = recognizer.recognize("/posts/12/67e55044-10b1-426f-9247-bb680e5fe0c8/2018-02-28/bob").unwrap();
+    assert_eq!(Some(&12u64), route_match.typed_params.get("id"));
+    assert_eq!(
+        Some(&"67e55044-10b1-426f-9247-bb680e5fe0c8".parse::<Uuid>().unwrap()),
+        route_match.typed_params.get("uuid")
+    );
+    assert_eq!(Some(&Date { year: 2018, month: 2, day: 28 }), route_match.typed_params.get("date"));
+    assert_eq!(None, route_match.typed_params.get::<u64>("name"));
+    assert_eq!(None, route_match.typed_params.get::<String>("id"));
+    assert_eq!(3, route_match.typed_params.len());
+
+    let invalid_param = |path: &str| recognizer.recognize(path).unwrap().invalid_param.unwrap();
+    assert_eq!(ParamError::Invalid {
+        name: "id".to_string(),
+        value: "18446744073709551616".to_string(),
+        reason: "not a valid `number`".to_string(),
+    }, invalid_param("/posts/18446744073709551616/67e55044-10b1-426f-9247-bb680e5fe0c8/2018-02-28/bob"));
+    assert_eq!("date", invalid_param("/posts/12/67e55044-10b1-426f-9247-bb680e5fe0c8/2018-02-30/bob").name());
+
+    let mut types = TypedStore::with_default_types();
+    types.insert("even", "[0-9]*[02468]");
+    types.converters.insert("even", |value| value.parse::<i32>().ok().map(|value| Box::new(value / 2) as TypedValue));
+    types.converters.insert_from_str::<u8>(Type::NUMBER_NAME);
+
+    let recognizer = Recognizer::with_converters(
+        "/half/{value:even}/{small:number}",
+        handler(),
+        Some(types.store()),
+        types.converters()
+    ).unwrap();
+
+    let route_match = recognizer.recognize("/half/84/255").unwrap();
+    assert_eq!(Some(&42i32), route_match.typed_params.get("value"));
+    assert_eq!(Some(&255u8), route_match.typed_params.get("small"));
+
+    assert!(recognizer.recognize("/half/84/256").is_none());
+    assert!(recognizer.recognize("/half/83/1").is_none());
+
+    types.converters.insert("name", |value| Some(Box::new(value.to_uppercase()) as TypedValue));
+    types.insert("name", "[^/]+");
+    let recognizer = Recognizer::with_converters("/users/{user:name}", handler(), Some(types.store()), types.converters()).unwrap();
+    let route_match = recognizer.recognize("/users/j%C3%B6rg").unwrap();
+    assert_eq!(Some(&"JÖRG".to_string()), route_match.typed_params.get("user"));
+}
+
 #[cfg(all(test, feature = "nightly"))]
 mod benches {
     extern crate test;
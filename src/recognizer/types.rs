@@ -4,6 +4,9 @@ use std::hash::Hash;
 use std::borrow::Borrow;
 use std::marker::{Send, Sync};
 
+use recognizer::converters::Converters;
+use recognizer::validators::Validators;
+
 pub type NameDefaultType = &'static str;
 pub type PatternDefaultType = &'static str;
 
@@ -15,6 +18,67 @@ impl Type {
 
     pub const NUMBER_NAME: NameDefaultType = "number";
     pub const NUMBER_PATTERN: PatternDefaultType = "[0-9]+";
+
+    pub const UUID_NAME: NameDefaultType = "uuid";
+    pub const UUID_PATTERN: PatternDefaultType =
+        "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}";
+
+    /// An ISO 8601 calendar date, the month and the day are checked by range only.
+    pub const DATE_NAME: NameDefaultType = "date";
+    pub const DATE_PATTERN: PatternDefaultType = "[0-9]{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12][0-9]|3[01])";
+
+    /// A signed decimal integer.
+    pub const INT_NAME: NameDefaultType = "int";
+    pub const INT_PATTERN: PatternDefaultType = "[-+]?[0-9]+";
+
+    pub const HEX_NAME: NameDefaultType = "hex";
+    pub const HEX_PATTERN: PatternDefaultType = "[0-9a-fA-F]+";
+
+    /// Lowercase words of letters and digits separated by single hyphens, like `hello-world-2`.
+    pub const SLUG_NAME: NameDefaultType = "slug";
+    pub const SLUG_PATTERN: PatternDefaultType = "[a-z0-9]+(?:-[a-z0-9]+)*";
+
+    pub const ALPHA_NAME: NameDefaultType = "alpha";
+    pub const ALPHA_PATTERN: PatternDefaultType = "[a-zA-Z]+";
+
+    pub const ALNUM_NAME: NameDefaultType = "alnum";
+    pub const ALNUM_PATTERN: PatternDefaultType = "[a-zA-Z0-9]+";
+
+    /// The URL-safe base64 alphabet without the padding.
+    pub const BASE64URL_NAME: NameDefaultType = "base64url";
+    pub const BASE64URL_PATTERN: PatternDefaultType = "[A-Za-z0-9_-]+";
+
+    /// A semantic version with the optional pre-release and build metadata, like `1.0.0-rc.1+42`.
+    pub const SEMVER_NAME: NameDefaultType = "semver";
+    pub const SEMVER_PATTERN: PatternDefaultType = concat!(
+        "(?:0|[1-9][0-9]*)\\.(?:0|[1-9][0-9]*)\\.(?:0|[1-9][0-9]*)",
+        "(?:-(?:0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*)(?:\\.(?:0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*))*)?",
+        "(?:\\+[0-9a-zA-Z-]+(?:\\.[0-9a-zA-Z-]+)*)?"
+    );
+}
+
+impl Type {
+    /// The default types only applied with the `{name:type}` syntax. A param named like one of
+    /// them, e.g. `{slug}`, keeps the `string` type unless the type is redefined.
+    const EXPLICIT_ONLY: &'static [(NameDefaultType, PatternDefaultType)] = &[
+        (Type::UUID_NAME, Type::UUID_PATTERN),
+        (Type::DATE_NAME, Type::DATE_PATTERN),
+        (Type::INT_NAME, Type::INT_PATTERN),
+        (Type::HEX_NAME, Type::HEX_PATTERN),
+        (Type::SLUG_NAME, Type::SLUG_PATTERN),
+        (Type::ALPHA_NAME, Type::ALPHA_PATTERN),
+        (Type::ALNUM_NAME, Type::ALNUM_PATTERN),
+        (Type::BASE64URL_NAME, Type::BASE64URL_PATTERN),
+        (Type::SEMVER_NAME, Type::SEMVER_PATTERN),
+    ];
+
+    /// Whether the type `name` with the `pattern` types the params with the same name and no
+    /// explicit type.
+    pub fn applies_by_name(name: &str, pattern: &str) -> bool {
+        !Type::EXPLICIT_ONLY.iter().any(|&(explicit_name, explicit_pattern)| {
+            explicit_name == name && explicit_pattern == pattern
+        })
+    }
 }
 
 pub trait TypeName: Eq + Hash + Borrow<str> + Send + Sync {}
@@ -23,6 +87,15 @@ impl<T: Eq + Hash + Borrow<str> + Send + Sync> TypeName for T {}
 pub trait TypePattern: AsRef<str> + Send + Sync {}
 impl<T: AsRef<str> + Send + Sync> TypePattern for T {}
 
+/// Glob types by name.
+///
+/// A type pattern can refer to the other types with the param syntax, the named references
+/// contribute nested params to the params typed by it:
+///
+/// ```ignore
+/// types.insert("ymd", "{year:number}-{month:number}-{day:number}");
+/// router.get(("/archive/{date:ymd}", &types), handler, None); // `date`, `date.year`, ...
+/// ```
 pub type Store<N, P> = HashMap<N, P>;
 
 pub type DefaultStore = Store<NameDefaultType, PatternDefaultType>;
@@ -36,6 +109,15 @@ impl DefaultStoreBuild for DefaultStore {
         let mut store = DefaultStore::new();
         store.insert(Type::STRING_NAME, Type::STRING_PATTERN);
         store.insert(Type::NUMBER_NAME, Type::NUMBER_PATTERN);
+        store.insert(Type::UUID_NAME, Type::UUID_PATTERN);
+        store.insert(Type::DATE_NAME, Type::DATE_PATTERN);
+        store.insert(Type::INT_NAME, Type::INT_PATTERN);
+        store.insert(Type::HEX_NAME, Type::HEX_PATTERN);
+        store.insert(Type::SLUG_NAME, Type::SLUG_PATTERN);
+        store.insert(Type::ALPHA_NAME, Type::ALPHA_PATTERN);
+        store.insert(Type::ALNUM_NAME, Type::ALNUM_PATTERN);
+        store.insert(Type::BASE64URL_NAME, Type::BASE64URL_PATTERN);
+        store.insert(Type::SEMVER_NAME, Type::SEMVER_PATTERN);
         store
     }
 }
@@ -45,6 +127,16 @@ pub trait GlobTypes {
     type Pattern: TypePattern;
 
     fn store(&self) -> &Store<Self::Name, Self::Pattern>;
+
+    /// Converters of the matched params, in addition to the default ones.
+    fn converters(&self) -> Option<&Converters> {
+        None
+    }
+
+    /// Validators of the matched params.
+    fn validators(&self) -> Option<&Validators> {
+        None
+    }
 }
 
 pub trait GlobTypesMut: GlobTypes {
@@ -72,7 +164,7 @@ impl<N, P> GlobTypesMut for Store<N, P>
     }
 }
 
-impl<'a, N, P> GlobTypes for &'a Store<N, P>
+impl<N, P> GlobTypes for &Store<N, P>
     where N: TypeName,
           P: TypePattern
 {
@@ -80,11 +172,100 @@ impl<'a, N, P> GlobTypes for &'a Store<N, P>
     type Pattern = P;
 
     fn store(&self) -> &Store<Self::Name, Self::Pattern> {
-        *self
+        self
+    }
+}
+
+impl<N, P> GlobTypes for &mut Store<N, P>
+    where N: TypeName,
+          P: TypePattern
+{
+    type Name = N;
+    type Pattern = P;
+
+    fn store(&self) -> &Store<Self::Name, Self::Pattern> {
+        self
+    }
+}
+
+impl<N, P> GlobTypesMut for &mut Store<N, P>
+    where N: TypeName,
+          P: TypePattern
+{
+    fn store_mut(&mut self) -> &mut Store<Self::Name, Self::Pattern> {
+        self
+    }
+}
+
+/// Glob types along with the converters and the validators of the matched params.
+///
+/// ```ignore
+/// let mut types = TypedStore::with_default_types();
+/// types.insert("id", "[1-9][0-9]*");
+/// types.converters.insert_from_str::<u32>("id");
+///
+/// router.get(("/users/{id}", &types), handler, None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TypedStore<N = NameDefaultType, P = PatternDefaultType>
+    where N: TypeName,
+          P: TypePattern
+{
+    pub patterns: Store<N, P>,
+    pub converters: Converters,
+    pub validators: Validators,
+}
+
+impl<N, P> TypedStore<N, P>
+    where N: TypeName,
+          P: TypePattern
+{
+    pub fn new(patterns: Store<N, P>, converters: Converters) -> Self {
+        TypedStore {
+            patterns,
+            converters,
+            validators: Validators::new(),
+        }
+    }
+
+    /// Register the glob type `name` matching the `pattern`.
+    pub fn insert(&mut self, name: N, pattern: P) -> &mut Self {
+        self.patterns.insert(name, pattern);
+        self
+    }
+}
+
+impl TypedStore<String, String> {
+    /// Add the glob types, the converters and the validators of `types`, replacing the ones
+    /// with the same names.
+    pub fn extend<T: GlobTypes>(&mut self, types: &T) {
+        for (name, pattern) in types.store().iter() {
+            self.patterns.insert(name.borrow().to_string(), pattern.as_ref().to_string());
+        }
+        if let Some(converters) = types.converters() {
+            self.converters.extend(converters);
+        }
+        if let Some(validators) = types.validators() {
+            self.validators.extend(validators);
+        }
+    }
+}
+
+impl DefaultStoreBuild for TypedStore<String, String> {
+    fn with_default_types() -> Self {
+        let mut types = TypedStore::default();
+        types.extend(&TypedStore::<NameDefaultType, PatternDefaultType>::with_default_types());
+        types
     }
 }
 
-impl<'a, N, P> GlobTypes for &'a mut Store<N, P>
+impl DefaultStoreBuild for TypedStore {
+    fn with_default_types() -> Self {
+        TypedStore::new(DefaultStore::with_default_types(), Converters::with_default_converters())
+    }
+}
+
+impl<N, P> GlobTypes for TypedStore<N, P>
     where N: TypeName,
           P: TypePattern
 {
@@ -92,16 +273,44 @@ impl<'a, N, P> GlobTypes for &'a mut Store<N, P>
     type Pattern = P;
 
     fn store(&self) -> &Store<Self::Name, Self::Pattern> {
-        *self
+        &self.patterns
+    }
+
+    fn converters(&self) -> Option<&Converters> {
+        Some(&self.converters)
+    }
+
+    fn validators(&self) -> Option<&Validators> {
+        Some(&self.validators)
     }
 }
 
-impl<'a, N, P> GlobTypesMut for &'a mut Store<N, P>
+impl<N, P> GlobTypesMut for TypedStore<N, P>
     where N: TypeName,
           P: TypePattern
 {
     fn store_mut(&mut self) -> &mut Store<Self::Name, Self::Pattern> {
-        &mut **self
+        &mut self.patterns
+    }
+}
+
+impl<N, P> GlobTypes for &TypedStore<N, P>
+    where N: TypeName,
+          P: TypePattern
+{
+    type Name = N;
+    type Pattern = P;
+
+    fn store(&self) -> &Store<Self::Name, Self::Pattern> {
+        &self.patterns
+    }
+
+    fn converters(&self) -> Option<&Converters> {
+        Some(&self.converters)
+    }
+
+    fn validators(&self) -> Option<&Validators> {
+        Some(&self.validators)
     }
 }
 
@@ -109,8 +318,87 @@ impl<'a, N, P> GlobTypesMut for &'a mut Store<N, P>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use regex::Regex;
+
+    fn assert_type(pattern: &str, valid: &[&str], invalid: &[&str]) {
+        let regex = Regex::new(&format!("^(?:{})$", pattern)).unwrap();
+        for value in valid {
+            assert!(regex.is_match(value), "`{}` should match `{}`", pattern, value);
+        }
+        for value in invalid {
+            assert!(!regex.is_match(value), "`{}` shouldn't match `{}`", pattern, value);
+        }
+    }
+
+    #[test]
+    fn default_types() {
+        assert_type(Type::STRING_PATTERN, &["a", "hello-world"], &["", "a/b", "a.b"]);
+        assert_type(Type::NUMBER_PATTERN, &["0", "0042", "18446744073709551616"], &["", "-1", "+1", "1a"]);
+        assert_type(Type::INT_PATTERN, &["0", "-1", "+42", "-0"], &["", "-", "+", "1-", "--1", "1.0"]);
+        assert_type(Type::HEX_PATTERN, &["0", "deadBEEF", "0123456789abcdef"], &["", "0x1f", "g", "-1"]);
+        assert_type(Type::UUID_PATTERN, &[
+            "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "67E55044-10B1-426F-9247-BB680E5FE0C8",
+        ], &[
+            "",
+            "67e5504410b1426f9247bb680e5fe0c8",
+            "67e55044-10b1-426f-9247-bb680e5fe0c",
+            "67e55044-10b1-426f-9247-bb680e5fe0c8a",
+            "g7e55044-10b1-426f-9247-bb680e5fe0c8",
+        ]);
+        assert_type(Type::SLUG_PATTERN, &["a", "hello-world", "v2-0"], &[
+            "", "-a", "a-", "a--b", "Hello", "a_b", "a b",
+        ]);
+        assert_type(Type::ALPHA_PATTERN, &["a", "Zz"], &["", "a1", "a-b", "é"]);
+        assert_type(Type::ALNUM_PATTERN, &["a", "Z9", "007"], &["", "a-1", "a_1", "é"]);
+        assert_type(Type::DATE_PATTERN, &["2018-01-01", "0000-12-31", "2018-02-29"], &[
+            "", "2018-1-01", "2018-00-10", "2018-13-10", "2018-01-00", "2018-01-32", "18-01-01", "2018/01/01",
+        ]);
+        assert_type(Type::BASE64URL_PATTERN, &["a", "AZaz09-_", "aGVsbG8"], &["", "a+b", "a/b", "aGVsbG8="]);
+        assert_type(Type::SEMVER_PATTERN, &[
+            "0.0.0", "1.2.3", "10.20.30", "1.0.0-alpha", "1.0.0-rc.1", "1.0.0-0.3.7",
+            "1.0.0-x-y.z", "1.0.0+20130313144700", "1.0.0-beta+exp.sha.5114f85",
+        ], &[
+            "", "1", "1.2", "1.2.3.4", "01.2.3", "1.02.3", "1.2.03", "1.2.3-", "1.2.3-01", "1.2.3+", "v1.2.3",
+        ]);
+    }
+
+    #[test]
+    fn applies_by_name() {
+        assert!(Type::applies_by_name(Type::NUMBER_NAME, Type::NUMBER_PATTERN));
+        assert!(Type::applies_by_name("id", "[0-9]+"));
+        assert!(!Type::applies_by_name(Type::SLUG_NAME, Type::SLUG_PATTERN));
+        assert!(!Type::applies_by_name(Type::DATE_NAME, Type::DATE_PATTERN));
+        assert!(Type::applies_by_name(Type::SLUG_NAME, "[a-z]+"));
+    }
+
+    #[test]
+    fn extend_typed_store() {
+        let mut types = TypedStore::<String, String>::with_default_types();
+        assert_eq!(Some(&Type::NUMBER_PATTERN.to_string()), types.patterns.get(Type::NUMBER_NAME));
+        assert!(types.converters.contains(Type::NUMBER_NAME));
+
+        let mut other = Store::<&str, &str>::default();
+        other.insert(Type::NUMBER_NAME, "[1-9][0-9]*");
+        other.insert("id", "[a-z]{8}");
+        types.extend(&other);
+        assert_eq!(Some(&"[1-9][0-9]*".to_string()), types.patterns.get(Type::NUMBER_NAME));
+        assert_eq!(Some(&"[a-z]{8}".to_string()), types.patterns.get("id"));
+        assert_eq!(Some(&Type::INT_PATTERN.to_string()), types.patterns.get(Type::INT_NAME));
+    }
+
+    #[test]
+    fn with_default_types() {
+        let types = DefaultStore::with_default_types();
+        for name in &[
+            Type::STRING_NAME, Type::NUMBER_NAME, Type::INT_NAME, Type::HEX_NAME, Type::UUID_NAME, Type::SLUG_NAME,
+            Type::ALPHA_NAME, Type::ALNUM_NAME, Type::DATE_NAME, Type::BASE64URL_NAME, Type::SEMVER_NAME,
+        ] {
+            assert!(types.contains_key(name), "{}", name);
+        }
+    }
 
-    fn get_glob_types_value<'a, T>(types: &'a T, key: <T as GlobTypes>::Name) -> &'a <T as GlobTypes>::Pattern
+    fn get_glob_types_value<T>(types: &T, key: <T as GlobTypes>::Name) -> &<T as GlobTypes>::Pattern
         where T: GlobTypes
     {
         types.store().get(key.borrow()).unwrap()
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Checks a matched param, percent-decoded, beyond its regex, `false` means that the param
+/// is invalid.
+pub type Validator = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// What a route does with a param rejected by a validator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationFailure {
+    /// The route doesn't match, so the next routes are tried.
+    #[default]
+    NoMatch,
+    /// The route matches, but the request results in a BadRequest response naming the param.
+    BadRequest,
+}
+
+/// Validators of the params, specialized by glob type name.
+///
+/// ```ignore
+/// let mut types = TypedStore::with_default_types();
+/// types.validators.insert("number", |value| value.len() <= 5);
+/// ```
+#[derive(Clone, Default)]
+pub struct Validators {
+    validators: HashMap<String, Validator>,
+}
+
+impl Validators {
+    pub fn new() -> Self {
+        Validators::default()
+    }
+
+    /// Register a validator for the glob type `name`.
+    pub fn insert<S, F>(&mut self, name: S, validator: F) -> &mut Self
+        where S: Into<String>,
+              F: Fn(&str) -> bool + Send + Sync + 'static
+    {
+        self.validators.insert(name.into(), Arc::new(validator));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Validator> {
+        self.validators.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.validators.contains_key(name)
+    }
+
+    /// Add all validators of `other`, replacing the ones with the same names.
+    pub fn extend(&mut self, other: &Validators) {
+        for (name, validator) in other.validators.iter() {
+            self.validators.insert(name.clone(), validator.clone());
+        }
+    }
+}
+
+impl fmt::Debug for Validators {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.validators.keys()).finish()
+    }
+}
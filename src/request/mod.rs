@@ -0,0 +1,118 @@
+//! Access to the matched route from the handlers of a `Router`.
+//!
+//! ```ignore
+//! use ferrum_router::RouteRequest;
+//!
+//! fn handler(request: &mut Request) -> FerrumResult<Response> {
+//!     let mut params = Params::new();
+//!     params.insert("query".to_string(), "next".to_string());
+//!     let next = request.uri_for("query", params)?;
+//!
+//!     let query = request.param("query")?;
+//!     Ok(Response::new().with_content(format!("{} -> {}", query, next), mime::TEXT_PLAIN))
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use ferrum::{Request, Response, FerrumError, StatusCode, Uri, mime};
+
+use router::{Router, RouterInner, MatchedRoute};
+use recognizer::Params;
+use uri_for::UriFor;
+
+/// Access to the matched route of a request.
+///
+/// Every method fails with `RouteError::NotRouted` if the request wasn't dispatched
+/// by a `Router`, instead of panicking.
+pub trait RouteRequest {
+    /// The params of the matched route.
+    fn params(&self) -> Result<&Params, RouteError>;
+
+    /// The percent-decoded value of the param `name` of the matched route, see `Params::get`.
+    fn param(&self, name: &str) -> Result<&str, RouteError>;
+
+    /// The route that handled the request.
+    fn matched_route(&self) -> Result<&MatchedRoute, RouteError>;
+
+    /// Generate a URI for the route `route_id` based off of the requested one, see `uri_for`.
+    fn uri_for(&self, route_id: &str, params: Params) -> Result<Uri, RouteError>;
+}
+
+impl RouteRequest for Request {
+    fn params(&self) -> Result<&Params, RouteError> {
+        self.extensions.get::<Router>().ok_or(RouteError::NotRouted)
+    }
+
+    fn param(&self, name: &str) -> Result<&str, RouteError> {
+        self.params()?.get(name)
+            .ok_or_else(|| RouteError::MissingParam(name.to_string()))
+    }
+
+    fn matched_route(&self) -> Result<&MatchedRoute, RouteError> {
+        self.extensions.get::<MatchedRoute>()
+            .map(|route| &**route)
+            .ok_or(RouteError::NotRouted)
+    }
+
+    fn uri_for(&self, route_id: &str, params: Params) -> Result<Uri, RouteError> {
+        let inner = self.extensions.get::<RouterInner>().ok_or(RouteError::NotRouted)?;
+        let (_, ref recognizer) = *inner.route_ids.get(route_id)
+            .ok_or_else(|| RouteError::UnknownRouteId(route_id.to_string()))?;
+
+        self.uri.generate(Some(&recognizer.glob), recognizer, params)
+            .map_err(|error| RouteError::InvalidUri(error.to_string()))
+    }
+}
+
+/// The error of an access to the matched route of a request.
+///
+/// A missing param converts into a `FerrumError` with a BadRequest response, the other errors
+/// are the mistakes of the server and come with an InternalServerError one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// The request wasn't dispatched by a `Router`.
+    NotRouted,
+    /// The matched route has no param with the name.
+    MissingParam(String),
+    /// No route is registered with the id.
+    UnknownRouteId(String),
+    /// The generated URI can't be parsed.
+    InvalidUri(String),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RouteError::NotRouted =>
+                f.write_str("The request wasn't dispatched by a router."),
+            RouteError::MissingParam(ref name) =>
+                write!(f, "Missing route param `{}`.", name),
+            RouteError::UnknownRouteId(ref id) =>
+                write!(f, "No route with the id `{}`.", id),
+            RouteError::InvalidUri(ref reason) =>
+                write!(f, "Invalid generated URI: {}.", reason),
+        }
+    }
+}
+
+impl Error for RouteError {
+    fn description(&self) -> &str { "Route access error" }
+}
+
+impl From<RouteError> for FerrumError {
+    fn from(error: RouteError) -> FerrumError {
+        let status = match error {
+            RouteError::MissingParam(_) => StatusCode::BadRequest,
+            _ => StatusCode::InternalServerError,
+        };
+        let response = Response::new()
+            .with_content(error.to_string(), mime::TEXT_PLAIN)
+            .with_status(status);
+        FerrumError::new(error, Some(response))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+use super::*;
+
+use ferrum::{Handler, Method, Request, Response, FerrumResult};
+use ferrum::request::HyperRequest;
+use router::Id;
+
+fn request(uri: &str) -> Request {
+    Request::new(HyperRequest::new(Method::Get, uri.parse().unwrap()))
+}
+
+#[test]
+fn test_route_access() {
+    fn handler(request: &mut Request) -> FerrumResult<Response> {
+        assert_eq!("42", request.param("id")?);
+        assert_eq!(Some("bob smith"), request.params()?.get("name"));
+        assert_eq!(RouteError::MissingParam("tab".to_string()), request.param("tab").unwrap_err());
+
+        let route = request.matched_route()?;
+        assert_eq!(Id::some("user"), route.id);
+        assert_eq!("/users/{id}/{name}", route.glob);
+
+        let mut params = Params::new();
+        params.insert("id".to_string(), "7".to_string());
+        params.insert("name".to_string(), "alice".to_string());
+        assert_eq!("http://localhost/users/7/alice", request.uri_for("user", params)?);
+        assert_eq!(RouteError::UnknownRouteId("post".to_string()), request.uri_for("post", Params::new()).unwrap_err());
+
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.get("/users/{id}/{name}", handler, Id::some("user"));
+
+    assert!(router.handle(&mut request("http://localhost/users/42/bob%20smith")).is_ok());
+}
+
+#[test]
+fn test_not_routed() {
+    let request = request("http://localhost/users/42");
+
+    assert_eq!(RouteError::NotRouted, request.params().unwrap_err());
+    assert_eq!(RouteError::NotRouted, request.param("id").unwrap_err());
+    assert_eq!(RouteError::NotRouted, request.matched_route().unwrap_err());
+    assert_eq!(RouteError::NotRouted, request.uri_for("user", Params::new()).unwrap_err());
+}
+
+#[test]
+fn test_into_ferrum_error() {
+    let error = FerrumError::from(RouteError::MissingParam("id".to_string()));
+    assert!(error.error.is::<RouteError>());
+    assert_eq!("Missing route param `id`.", error.to_string());
+    assert_eq!(StatusCode::BadRequest, error.response.unwrap().status);
+
+    let error = FerrumError::from(RouteError::NotRouted);
+    assert_eq!("The request wasn't dispatched by a router.", error.to_string());
+    assert_eq!(StatusCode::InternalServerError, error.response.unwrap().status);
+}
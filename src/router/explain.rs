@@ -0,0 +1,198 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ferrum::Method;
+use regex::{self, Regex};
+
+use recognizer::{GlobNode, ParamError, Recognize, Recognizer};
+use router::MatchedRoute;
+
+/// Why the routes of a `Router` match a path or not, see `Router::explain`.
+///
+/// It is displayed as a plain text report:
+///
+/// ```text
+/// GET /users/bob
+///   #0 GET /users/{id:number} (user): not matched
+///     regex: ^/users/(?P<id>[0-9]+)/?$
+///     `{id:number}` doesn't match `bob` after `/users/`
+///   #1 any /{*path}: matched
+///     regex: ^/(?P<path>.*)/?$
+///     params: path = `users/bob`
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub method: Method,
+    pub path: String,
+    /// The routes in the order they are tried for the method.
+    pub routes: Vec<RouteExplanation>,
+}
+
+/// Why a route matches a path or not.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteExplanation {
+    /// The registered route, `None` for a recognizer which isn't registered in a router.
+    pub route: Option<Arc<MatchedRoute>>,
+    pub glob: String,
+    /// The regex the glob is compiled to.
+    pub regex: String,
+    /// The route matches the path, the params captured by the regex are valid too.
+    pub matched: bool,
+    /// The params captured by the regex, if it matches the path.
+    pub params: Vec<(String, String)>,
+    /// The param rejected by a validator or a default converter of the route, which answers
+    /// the request with a BadRequest response, see `ValidationFailure::BadRequest`.
+    pub invalid_param: Option<ParamError>,
+    /// Where the path stops matching the glob, if the regex doesn't match it.
+    pub failure: Option<MatchFailure>,
+}
+
+/// Where a path stops matching a glob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchFailure {
+    /// The beginning of the glob which matches the beginning of the path.
+    pub matched_glob: String,
+    /// The beginning of the path matched by `matched_glob`.
+    pub matched_path: String,
+    /// The segment of the glob which doesn't match the rest of the path, `None` if the path
+    /// goes on after the whole glob.
+    pub failed_segment: Option<String>,
+    /// The path after `matched_path`.
+    pub rest: String,
+}
+
+impl RouteExplanation {
+    pub fn new(recognizer: &Recognizer, path: &str) -> Self {
+        let captures = recognizer.glob_regex.captures(path);
+        let params = match captures {
+            Some(ref captures) => recognizer.param_groups.iter()
+                .filter_map(|(group, name)| {
+                    captures.name(group).map(|value| (name.to_string(), value.as_str().to_string()))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let route_match = if captures.is_some() { recognizer.recognize(path) } else { None };
+        let invalid_param = route_match.as_ref().and_then(|route_match| route_match.invalid_param.clone());
+
+        RouteExplanation {
+            route: recognizer.route.clone(),
+            glob: recognizer.glob.source.clone(),
+            regex: recognizer.glob_regex.as_str().to_string(),
+            matched: route_match.is_some() && invalid_param.is_none(),
+            params,
+            invalid_param,
+            failure: if captures.is_none() { Some(MatchFailure::new(recognizer, path)) } else { None },
+        }
+    }
+}
+
+impl MatchFailure {
+    /// Find the longest beginning of the glob of the `recognizer` matching the beginning of
+    /// the `path`, by the path segments of its literal text and by its other nodes.
+    pub fn new(recognizer: &Recognizer, path: &str) -> Self {
+        let segments = glob_segments(recognizer);
+
+        let mut pattern = String::new();
+        let mut matched_count = 0;
+        let mut matched_end = 0;
+        for (_, segment_pattern) in segments.iter() {
+            let candidate = format!("{}{}", pattern, segment_pattern);
+            let found = Regex::new(&format!("^(?:{})", candidate)).ok()
+                .and_then(|regex| regex.find(path).map(|found| found.end()));
+            match found {
+                Some(end) => {
+                    pattern = candidate;
+                    matched_count += 1;
+                    matched_end = end;
+                },
+                None => break,
+            }
+        }
+
+        MatchFailure {
+            matched_glob: segments[..matched_count].iter().map(|(glob, _)| glob.as_str()).collect(),
+            matched_path: path[..matched_end].to_string(),
+            failed_segment: segments.get(matched_count).map(|(glob, _)| glob.clone()),
+            rest: path[matched_end..].to_string(),
+        }
+    }
+}
+
+/// The glob text and the regex of the segments of the glob, the literal text is split before
+/// each `/` unless it is a raw regex.
+fn glob_segments(recognizer: &Recognizer) -> Vec<(String, String)> {
+    let raw_regex = recognizer.glob.raw_regex;
+    let mut segments = Vec::new();
+    for (node, pattern) in recognizer.glob.nodes.iter().zip(recognizer.node_patterns.iter()) {
+        match *node {
+            GlobNode::Literal(ref text) if !raw_regex => {
+                let mut start = 0;
+                for (index, _) in text.match_indices('/').chain(Some((text.len(), ""))) {
+                    if index > start {
+                        let literal = GlobNode::Literal(text[start..index].to_string());
+                        segments.push((literal.to_glob(false), regex::escape(&text[start..index])));
+                        start = index;
+                    }
+                }
+            },
+            _ => segments.push((node.to_glob(raw_regex), pattern.clone())),
+        }
+    }
+    segments
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", self.method, self.path)?;
+        if self.routes.is_empty() {
+            writeln!(f, "  no routes for the method")?;
+        }
+        for route in self.routes.iter() {
+            write!(f, "{}", route)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RouteExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match (self.matched, &self.invalid_param, &self.failure) {
+            (true, _, _) => "matched".to_string(),
+            (false, Some(error), _) => format!("invalid param `{}`, answered with BadRequest", error.name()),
+            (false, None, None) => "rejected by the params".to_string(),
+            (false, None, Some(_)) => "not matched".to_string(),
+        };
+        match self.route {
+            Some(ref route) => {
+                write!(f, "  #{} {} {}", route.index, route.method_name(), self.glob)?;
+                if let Some(ref id) = route.id {
+                    write!(f, " ({})", id)?;
+                }
+                writeln!(f, ": {}", status)?;
+            },
+            None => writeln!(f, "  {}: {}", self.glob, status)?,
+        }
+        writeln!(f, "    regex: {}", self.regex)?;
+
+        if !self.params.is_empty() {
+            let params = self.params.iter()
+                .map(|(name, value)| format!("{} = `{}`", name, value))
+                .collect::<Vec<_>>();
+            writeln!(f, "    params: {}", params.join(", "))?;
+        }
+        if let Some(ref error) = self.invalid_param {
+            writeln!(f, "    {}", error)?;
+        }
+        if let Some(ref failure) = self.failure {
+            match failure.failed_segment {
+                Some(ref segment) =>
+                    writeln!(f, "    `{}` doesn't match `{}` after `{}`", segment, failure.rest, failure.matched_path)?,
+                None =>
+                    writeln!(f, "    the path goes on with `{}` after the glob", failure.rest)?,
+            }
+        }
+        Ok(())
+    }
+}
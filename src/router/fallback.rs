@@ -0,0 +1,16 @@
+use ferrum::Method;
+use ferrum::typemap::Key;
+
+/// The request no route matches, inserted into the request extensions for the fallback
+/// handlers of a router, see `Router::fallback`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unmatched {
+    /// The method of the request, HEAD even if the GET routes are tried for it.
+    pub method: Method,
+    /// The path of the request.
+    pub path: String,
+}
+
+impl Key for Unmatched {
+    type Value = Unmatched;
+}
@@ -37,9 +37,9 @@ impl<'a> From<&'a str> for Id {
     }
 }
 
-impl Into<String> for Id {
-    fn into(self) -> String {
-        self.0
+impl From<Id> for String {
+    fn from(from: Id) -> Self {
+        from.0
     }
 }
 
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use ferrum::Method;
+use ferrum::typemap::Key;
+
+use router::Id;
+
+/// The route that handled a request, inserted into the request extensions by the router.
+///
+/// It labels the logs and metrics with the route rather than the raw path:
+///
+/// ```ignore
+/// let route = request.extensions.get::<MatchedRoute>().unwrap();
+/// println!("{} {} -> #{} {}", route.method_name(), request.uri, route.index, route.glob);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedRoute {
+    /// The id the route is registered with.
+    pub id: Option<Id>,
+    /// The glob of the route as it is registered, e.g. `/users/{id}`.
+    pub glob: String,
+    /// The method of the route, `None` for a route registered with `Router::any`.
+    pub method: Option<Method>,
+    /// The position of the route in the registration order of the router, starting at zero.
+    pub index: usize,
+}
+
+impl MatchedRoute {
+    /// The name of the route method, `any` for a route registered with `Router::any`.
+    pub fn method_name(&self) -> &str {
+        self.method.as_ref().map(|method| method.as_ref()).unwrap_or("any")
+    }
+}
+
+impl Key for MatchedRoute {
+    type Value = Arc<MatchedRoute>;
+}
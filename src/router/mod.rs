@@ -2,15 +2,31 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 
 use ferrum::{Request, Response, Handler, FerrumResult, FerrumError};
-use ferrum::{header, Method, StatusCode};
+use ferrum::{header, mime, Method, StatusCode};
 use ferrum::typemap::Key;
 
 use recognizer::{Glob, GlobTypes, Recognizer, Recognize, RouteMatch, Params};
+use recognizer::{TypedParams, TypedStore, DefaultStoreBuild};
+use cors::{self, Cors, CorsScope};
+use metrics::Metrics;
 
 pub mod id;
+pub mod matched;
+pub mod observer;
+pub mod explain;
+pub mod suggest;
+pub mod fallback;
+pub mod problem;
 pub use self::id::*;
+pub use self::matched::*;
+pub use self::observer::*;
+pub use self::explain::*;
+pub use self::suggest::Suggestion;
+pub use self::fallback::*;
+pub use self::problem::{Problem, ErrorRenderer, ProblemJson, ProblemHtml};
 
 pub struct RouterInner {
     /// The routers, specialized by method.
@@ -21,6 +37,41 @@ pub struct RouterInner {
 
     /// Used in URI generation.
     pub route_ids: HashMap<Id, (String, Arc<Recognizer>)>,
+
+    /// CORS policies for the paths matching a glob, in registration order.
+    pub cors_scopes: Vec<CorsScope>,
+
+    /// CORS policy for the paths not matching any of `cors_scopes`.
+    pub cors: Option<Cors>,
+
+    /// Glob types of the routes registered after they are added, over the default ones.
+    pub types: TypedStore<String, String>,
+
+    /// The stats of the matched routes, if recorded.
+    pub metrics: Option<Metrics>,
+
+    /// The observers of the routing decisions, in registration order.
+    pub observers: Vec<Arc<dyn RouteObserver>>,
+
+    /// Explain the unmatched requests in the NotFound responses, see `Router::dev_mode`.
+    pub dev_mode: bool,
+
+    /// List the routes close to the unmatched requests in the NotFound responses,
+    /// see `Router::suggestions`.
+    pub suggestions: bool,
+
+    /// Answer MethodNotAllowed to the requests which paths are routed for other methods only,
+    /// see `Router::method_not_allowed`.
+    pub method_not_allowed: bool,
+
+    /// Fallback handlers for the unmatched paths matching a glob, in registration order.
+    pub fallback_scopes: Vec<Arc<Recognizer>>,
+
+    /// Fallback handler for the unmatched requests not covered by `fallback_scopes`.
+    pub fallback: Option<Box<dyn Handler>>,
+
+    /// The renderers of the routing errors by media type, see `Router::error_renderer`.
+    pub error_renderers: Vec<(mime::Mime, Box<dyn ErrorRenderer>)>,
 }
 
 /// `Router` provides an interface for creating complex routes as middleware
@@ -42,6 +93,20 @@ impl Router {
                 routers: HashMap::new(),
                 wildcard: Vec::new(),
                 route_ids: HashMap::new(),
+                cors_scopes: Vec::new(),
+                cors: None,
+                types: TypedStore::with_default_types(),
+                metrics: None,
+                observers: Vec::new(),
+                dev_mode: false,
+                suggestions: cfg!(debug_assertions),
+                method_not_allowed: false,
+                fallback_scopes: Vec::new(),
+                fallback: None,
+                error_renderers: vec![
+                    (problem::PROBLEM_JSON.parse().unwrap(), Box::new(ProblemJson)),
+                    (mime::TEXT_HTML, Box::new(ProblemHtml)),
+                ],
             })
         }
     }
@@ -54,7 +119,9 @@ impl Router {
     ///
     /// `route` supports glob patterns based on the rust regex and uses `{name}` (`{name: typename}`,
     /// `{name: pattern}`) for matching storing named segment of the request url in the `Params`
-    /// object, which is stored in the request `extensions`.
+    /// object, which is stored in the request `extensions`. The text outside of the params is
+    /// matched literally, unless the glob is marked with `Glob::raw_regex`. The type names are
+    /// looked up in the types of the glob, then in the router ones (see `glob_type`).
     ///
     /// For instance, to route `Get` requests on any route matching
     /// `/users/{userid:[0-9]+}/{friendid:[0-9]+}` and store `userid` and `friend` in
@@ -79,11 +146,8 @@ impl Router {
               S: AsRef<[u8]>,
               T: GlobTypes,
     {
-        let glob = glob.into();
-        let types = glob.types().map(|types| types.store());
-        let recognizer = Arc::new(
-            Recognizer::new(glob.path(), Box::new(handler), types).unwrap()
-        );
+        let glob = self.layer_types(glob.into());
+        let recognizer = self.recognizer(&glob, Box::new(handler), route_id.clone(), Some(method.clone()));
 
         if let Some(route_id) = route_id {
             self.route_id(route_id, glob.path(), recognizer.clone());
@@ -91,17 +155,91 @@ impl Router {
 
         self.mut_inner().routers
             .entry(method)
-            .or_insert(Vec::new())
+            .or_default()
             .push(recognizer);
         self
     }
 
+    /// Register the glob type `name` for the routes added after, replacing a default type
+    /// with the same name.
+    ///
+    /// ```ignore
+    /// router.glob_type("id", "[1-9][0-9]*");
+    /// router.get("/users/{id}", handler, None);
+    /// ```
+    pub fn glob_type<N, P>(&mut self, name: N, pattern: P) -> &mut Router
+        where N: Into<String>,
+              P: Into<String>
+    {
+        self.mut_inner().types.insert(name.into(), pattern.into());
+        self
+    }
+
+    /// Register the validator of the params of the glob type `name`, for the routes added after.
+    ///
+    /// The validator runs after the glob regex matches, see `Glob::on_validation_failure`
+    /// for what a route does with a rejected param.
+    pub fn type_validator<N, F>(&mut self, name: N, validator: F) -> &mut Router
+        where N: Into<String>,
+              F: Fn(&str) -> bool + Send + Sync + 'static
+    {
+        self.mut_inner().types.validators.insert(name, validator);
+        self
+    }
+
+    /// Register the glob types (with their converters and validators) of `types` for the routes
+    /// added after.
+    pub fn glob_types<T: GlobTypes>(&mut self, types: &T) -> &mut Router {
+        self.mut_inner().types.extend(types);
+        self
+    }
+
+    /// The glob types of the routes added after, the types of a route glob override them.
+    pub fn types(&self) -> &TypedStore<String, String> {
+        &self.inner.types
+    }
+
+    fn layer_types<S, T>(&self, glob: Glob<S, T>) -> Glob<S, TypedStore<String, String>>
+        where S: AsRef<[u8]>,
+              T: GlobTypes,
+    {
+        let mut types = self.inner.types.clone();
+        if let Some(glob_types) = glob.types() {
+            types.extend(glob_types);
+        }
+        glob.with_types(Some(types))
+    }
+
+    fn recognizer<S>(
+        &self,
+        glob: &Glob<S, TypedStore<String, String>>,
+        handler: Box<dyn Handler>,
+        route_id: Option<Id>,
+        method: Option<Method>
+    ) -> Arc<Recognizer>
+        where S: AsRef<[u8]>
+    {
+        let mut recognizer = Recognizer::from_glob(glob, handler).unwrap_or_else(|error| panic!("{}", error));
+        let index = self.inner.routers.values().map(Vec::len).sum::<usize>() + self.inner.wildcard.len();
+        let route = Arc::new(MatchedRoute {
+            id: route_id,
+            glob: recognizer.glob.source.clone(),
+            method,
+            index,
+        });
+        for observer in self.inner.observers.iter() {
+            observer.on_register(&route);
+        }
+        recognizer.route = Some(route);
+        Arc::new(recognizer)
+    }
+
     fn route_id(&mut self, id: Id, glob_path: &[u8], recognizer: Arc<Recognizer>) {
         let inner = self.mut_inner();
-        let ref mut route_ids = inner.route_ids;
+        let route_ids = &mut inner.route_ids;
 
         match route_ids.get(&id) {
-            Some(&(ref other_glob_path, _)) if glob_path != other_glob_path.as_bytes() =>
+            Some((other_glob_path, _)) if glob_path != other_glob_path.as_bytes() =>
                 panic!("Duplicate route_id: {}", id),
             _ => ()
         };
@@ -187,11 +325,8 @@ impl Router {
               S: AsRef<[u8]>,
               T: GlobTypes,
     {
-        let glob = glob.into();
-        let types = glob.types().map(|types| types.store());
-        let recognizer = Arc::new(
-            Recognizer::new(glob.path(), Box::new(handler), types).unwrap()
-        );
+        let glob = self.layer_types(glob.into());
+        let recognizer = self.recognizer(&glob, Box::new(handler), route_id.clone(), None);
 
         if let Some(route_id) = route_id {
             self.route_id(route_id, glob.path(), recognizer.clone());
@@ -201,15 +336,223 @@ impl Router {
         self
     }
 
-    fn recognize(&self, method: &Method, path: &str) -> Option<RouteMatch> {
+    /// Set the router-wide CORS policy.
+    ///
+    /// The policy applies to every path not covered by a more specific `cors_for` policy.
+    ///
+    /// The policy answers the preflight requests before the routes are dispatched, except for
+    /// the paths with an explicit `options` route, see `Cors`.
+    pub fn cors(&mut self, policy: Cors) -> &mut Router {
+        self.mut_inner().cors = Some(policy);
+        self
+    }
+
+    /// Set the CORS policy for the paths matching a glob pattern.
+    ///
+    /// Policies are checked in registration order and the first matching one wins, so a scope
+    /// like `/api/{tail:.*}` can be refined by registering a policy for a single route before it:
+    ///
+    /// ```ignore
+    /// router.cors_for("/api/public", Cors::permissive());
+    /// router.cors_for("/api/{tail:.*}", Cors::new().with_origin("https://example.com"));
+    /// ```
+    pub fn cors_for<G, S, T>(&mut self, glob: G, policy: Cors) -> &mut Router
+        where G: Into<Glob<S, T>>,
+              S: AsRef<[u8]>,
+              T: GlobTypes,
+    {
+        let glob = self.layer_types(glob.into());
+        let (glob_regex, _) = glob.parse().unwrap_or_else(|error| panic!("{}", error));
+
+        self.mut_inner().cors_scopes.push(CorsScope { glob_regex, policy });
+        self
+    }
+
+    /// Record the request count, error count and latency of the matched routes into `metrics`.
+    ///
+    /// ```ignore
+    /// let metrics = Metrics::new();
+    /// router.metrics(metrics.clone());
+    /// router.get("/metrics", metrics, None);
+    /// ```
+    pub fn metrics(&mut self, metrics: Metrics) -> &mut Router {
+        self.mut_inner().metrics = Some(metrics);
+        self
+    }
+
+    /// Add an observer of the routing decisions, it is notified of the routes registered
+    /// before it first.
+    pub fn observer<O: RouteObserver>(&mut self, observer: O) -> &mut Router {
+        let mut routes = self.inner.routers.values()
+            .flat_map(|recognizers| recognizers.iter())
+            .chain(self.inner.wildcard.iter())
+            .filter_map(|recognizer| recognizer.route.as_ref())
+            .collect::<Vec<_>>();
+        routes.sort_by_key(|route| route.index);
+        for route in routes {
+            observer.on_register(route);
+        }
+
+        self.mut_inner().observers.push(Arc::new(observer));
+        self
+    }
+
+    /// Explain the NotFound responses with the report of `explain` in their bodies.
+    ///
+    /// The report reveals the routes, so it is meant for development only.
+    pub fn dev_mode(&mut self, enabled: bool) -> &mut Router {
+        self.mut_inner().dev_mode = enabled;
+        self
+    }
+
+    /// List the routes close to the path of an unmatched request in its NotFound response, see
+    /// `suggest`. The list is passed to the error renderers as `Problem::suggestions`.
+    ///
+    /// It is enabled by default in the debug builds only, the dev mode report replaces it.
+    pub fn suggestions(&mut self, enabled: bool) -> &mut Router {
+        self.mut_inner().suggestions = enabled;
+        self
+    }
+
+    /// Answer MethodNotAllowed with an `Allow` header, instead of NotFound, to the requests which
+    /// paths are routed for other methods only, see `NoRoute::is_method_not_allowed`.
+    ///
+    /// It is disabled by default, so that the code expecting a NotFound response for any
+    /// unmatched request keeps working.
+    pub fn method_not_allowed(&mut self, enabled: bool) -> &mut Router {
+        self.mut_inner().method_not_allowed = enabled;
+        self
+    }
+
+    /// Render the body of the routing error responses with the `renderer` for the requests
+    /// accepting the `media_type`, replacing the renderer of the media type if any.
+    ///
+    /// The renderer is picked by the `Accept` header of the request, the first one registered
+    /// if the request has no such header or accepts none of the media types. By default, the
+    /// errors are rendered as `application/problem+json` RFC 7807 documents, or as `text/html`
+    /// pages. The dev mode report replaces them.
+    pub fn error_renderer<R: ErrorRenderer>(&mut self, media_type: mime::Mime, renderer: R) -> &mut Router {
+        let renderers = &mut self.mut_inner().error_renderers;
+        let renderer: Box<dyn ErrorRenderer> = Box::new(renderer);
+        match renderers.iter().position(|(registered, _)| *registered == media_type) {
+            Some(index) => renderers[index].1 = renderer,
+            None => renderers.push((media_type, renderer)),
+        }
+        self
+    }
+
+    /// Remove the error renderers, including the default ones, so that the routing error
+    /// responses have empty bodies until a renderer is added.
+    pub fn clear_error_renderers(&mut self) -> &mut Router {
+        self.mut_inner().error_renderers.clear();
+        self
+    }
+
+    /// The routes accepting the `method` close to the `path`, the closest first.
+    pub fn suggest(&self, method: &Method, path: &str) -> Vec<Suggestion> {
+        suggest::suggest(self.method_recognizers(method).into_iter(), path)
+    }
+
+    /// The recognizers tried for the `method` in order: the ones of the method, the ones of any
+    /// method and, for HEAD, the GET ones.
+    fn method_recognizers(&self, method: &Method) -> Vec<&Arc<Recognizer>> {
+        let routers = &self.inner.routers;
+        let mut recognizers = routers.get(method)
+            .into_iter()
+            .flat_map(|recognizers| recognizers.iter())
+            .chain(self.inner.wildcard.iter())
+            .collect::<Vec<_>>();
+        if *method == Method::Head {
+            recognizers.extend(routers.get(&Method::Get).into_iter().flat_map(|recognizers| recognizers.iter()));
+        }
+        recognizers
+    }
+
+    /// Explain why each route matches the `path` with the `method` or not, in the order the
+    /// routes are tried: the ones of the method, the ones of any method and, for HEAD, the
+    /// GET ones.
+    pub fn explain(&self, method: &Method, path: &str) -> Explanation {
+        let routes = self.method_recognizers(method).into_iter()
+            .map(|recognizer| RouteExplanation::new(recognizer, path))
+            .collect();
+
+        Explanation {
+            method: method.clone(),
+            path: path.to_string(),
+            routes,
+        }
+    }
+
+    /// Set the handler of the requests no route matches, instead of the NoRoute error.
+    ///
+    /// The handler gets the request with the `Unmatched` extension and the extensions of
+    /// a matched route, with no params.
+    ///
+    /// ```ignore
+    /// router.fallback(|request: &mut Request| {
+    ///     let path = &request.extensions.get::<Unmatched>().unwrap().path;
+    ///     Ok(Response::new().with_content(format!("No page at {}", path), mime::TEXT_PLAIN)
+    ///         .with_status(StatusCode::NotFound))
+    /// });
+    /// ```
+    pub fn fallback<H: Handler>(&mut self, handler: H) -> &mut Router {
+        self.mut_inner().fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Set the handler of the requests no route matches for the paths matching a glob, with
+    /// the params of the glob.
+    ///
+    /// The scopes are checked in registration order and the first matching one wins, before
+    /// the router-wide `fallback`:
+    ///
+    /// ```ignore
+    /// router.fallback_for("/api/{*path}", api_not_found);
+    /// router.fallback(page_not_found);
+    /// ```
+    pub fn fallback_for<G, H, S, T>(&mut self, glob: G, handler: H) -> &mut Router
+        where G: Into<Glob<S, T>>,
+              H: Handler,
+              S: AsRef<[u8]>,
+              T: GlobTypes,
+    {
+        let glob = self.layer_types(glob.into());
+        let recognizer = Recognizer::from_glob(&glob, Box::new(handler)).unwrap_or_else(|error| panic!("{}", error));
+
+        self.mut_inner().fallback_scopes.push(Arc::new(recognizer));
+        self
+    }
+
+    fn cors_policy(&self, path: &str) -> Option<&Cors> {
+        self.inner.cors_scopes.iter()
+            .find(|scope| scope.glob_regex.is_match(path))
+            .map(|scope| &scope.policy)
+            .or(self.inner.cors.as_ref())
+    }
+
+    /// The matches of the routes for the `path` with the `method`, in the order they are
+    /// tried: the ones of the method, then the ones of any method.
+    fn matches<'a: 'p, 'p>(&'a self, method: &Method, path: &'p str) -> impl Iterator<Item = RouteMatch<'a>> + 'p {
         self.inner.routers
             .get(method)
-            .and_then(|recognizers| recognizers.recognize(path))
-            .or(self.inner.wildcard.recognize(path))
+            .into_iter()
+            .flat_map(|recognizers| recognizers.iter())
+            .chain(self.inner.wildcard.iter())
+            .filter_map(move |recognizer| recognizer.recognize(path))
     }
 
-    fn handle_options(&self, path: &str) -> Response {
-        static METHODS: &'static [Method] = &[
+    #[cfg(test)]
+    fn recognize(&self, method: &Method, path: &str) -> Option<RouteMatch<'_>> {
+        self.matches(method, path).next()
+    }
+
+    /// Compute the methods a path can be dispatched with, in the order used for `Allow` headers.
+    ///
+    /// Routes registered with `any` make every standard method available. `HEAD` is available
+    /// along with `GET`, and `OPTIONS` is always available for a routed path since the router
+    /// answers it when no explicit `options` route does.
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        static METHODS: &[Method] = &[
             Method::Get,
             Method::Post,
             Method::Put,
@@ -218,35 +561,261 @@ impl Router {
             Method::Patch
         ];
 
+        let is_any = self.inner.wildcard.recognize(path).is_some();
+        let is_routed = |method: &Method| {
+            self.inner.routers.get(method)
+                .map(|recognizers| recognizers.recognize(path).is_some())
+                .unwrap_or(false)
+        };
+
         // Get all the available methods and return them.
-        let mut options = vec![];
+        let mut options = METHODS.iter()
+            .filter(|method| is_any || is_routed(method))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut extensions = self.inner.routers.keys()
+            .filter(|method| !METHODS.contains(method) && **method != Method::Options)
+            .filter(|method| is_routed(method))
+            .cloned()
+            .collect::<Vec<_>>();
+        extensions.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        options.extend(extensions);
 
-        for method in METHODS.iter() {
-            self.inner.routers.get(method).map(|recognizers| {
-                if let Some(_) = recognizers.recognize(path) {
-                    options.push(method.clone());
-                }
-            });
-        }
         // If GET is there, HEAD is also there.
         if options.contains(&Method::Get) && !options.contains(&Method::Head) {
             options.push(Method::Head);
         }
+        if !options.is_empty() || is_routed(&Method::Options) {
+            options.push(Method::Options);
+        }
+        options
+    }
 
+    /// Respond to an `OPTIONS` request for a path no route handles explicitly.
+    ///
+    /// Registering an `options` (or `any`) route for a path overrides this response. Only an
+    /// `options` route overrides the answer of a CORS policy to a preflight request.
+    fn handle_options(&self, path: &str) -> Response {
         let mut response = Response::new().with_status(StatusCode::Ok);
-        response.headers.set(header::Allow(options));
+        response.headers.set(header::Allow(self.allowed_methods(path)));
         response
     }
 
-    fn handle_method(&self, request: &mut Request) -> Option<FerrumResult<Response>> {
-        if let Some(matched) = self.recognize(&request.method, request.uri.path()) {
-            request.extensions.insert::<Router>(matched.params);
-            request.extensions.insert::<RouterInner>(self.inner.clone());
-            Some(matched.handler.handle(request))
+    fn handle_cors(&self, request: &mut Request, policy: &Cors) -> FerrumResult<Response> {
+        if cors::is_preflight(request) && !self.has_options_route(request.uri.path()) {
+            let allowed = self.allowed_methods(request.uri.path());
+            return policy.preflight(request, &allowed);
+        }
+
+        match policy.check_origin(request)? {
+            Some(origin) => match self.handle_routes(request) {
+                Ok(mut response) => {
+                    policy.apply(&origin, &mut response.headers);
+                    Ok(response)
+                },
+                Err(mut error) => {
+                    if let Some(ref mut response) = error.response {
+                        policy.apply(&origin, &mut response.headers);
+                    }
+                    Err(error)
+                }
+            },
+            None => self.handle_routes(request)
+        }
+    }
+
+    /// Check whether an explicit `options` route matches the path, it answers the CORS
+    /// preflight requests instead of the policy.
+    fn has_options_route(&self, path: &str) -> bool {
+        self.inner.routers.get(&Method::Options)
+            .into_iter()
+            .flat_map(|recognizers| recognizers.iter())
+            .any(|recognizer| recognizer.recognize(path).is_some())
+    }
+
+    fn handle_routes(&self, request: &mut Request) -> FerrumResult<Response> {
+        self.handle_method(request).unwrap_or_else(||
+            match request.method {
+                Method::Options => Ok(self.handle_options(request.uri.path())),
+                // For HEAD, fall back to GET. Hyper ensures no response body is written.
+                Method::Head => {
+                    request.method = Method::Get;
+                    self.handle_method(request).unwrap_or_else(|| {
+                        request.method = Method::Head;
+                        self.handle_unmatched(request)
+                    })
+                }
+                _ => self.handle_unmatched(request)
+            }
+        )
+    }
+
+    fn handle_unmatched(&self, request: &mut Request) -> FerrumResult<Response> {
+        // Computed once for the observers and the NotFound response, not at all if a fallback
+        // handles the request without observers.
+        let mut no_route = None;
+        if !self.inner.observers.is_empty() {
+            let error = self.no_route(&request.method, request.uri.path());
+            let miss = match error {
+                ref error if error.is_method_not_allowed() => {
+                    RouteMiss::MethodNotAllowed(error.allowed_methods.clone())
+                },
+                NoRoute { trailing_slash: Some(ref path), .. } | NoRoute { case_variant: Some(ref path), .. } => {
+                    RouteMiss::Redirectable(path.clone())
+                },
+                _ => RouteMiss::NotFound,
+            };
+            for observer in self.inner.observers.iter() {
+                observer.on_miss(request, &miss);
+            }
+            no_route = Some(error);
+        }
+
+        match self.handle_fallback(request) {
+            Some(result) => result,
+            None => {
+                let error = no_route.unwrap_or_else(|| self.no_route(&request.method, request.uri.path()));
+                self.not_found(request, error)
+            }
+        }
+    }
+
+    fn handle_fallback(&self, request: &mut Request) -> Option<FerrumResult<Response>> {
+        let path = request.uri.path().to_string();
+        let (handler, params, typed_params) = match self.inner.fallback_scopes.recognize(&path) {
+            Some(matched) => (matched.handler, matched.params, matched.typed_params),
+            None => (&**self.inner.fallback.as_ref()?, Params::new(), TypedParams::new()),
+        };
+
+        request.extensions.insert::<Unmatched>(Unmatched { method: request.method.clone(), path });
+        request.extensions.insert::<Router>(params);
+        request.extensions.insert::<TypedParams>(typed_params);
+        request.extensions.insert::<RouterInner>(self.inner.clone());
+        Some(handler.handle(request))
+    }
+
+    fn not_found(&self, request: &Request, error: NoRoute) -> FerrumResult<Response> {
+        let mut problem = Problem::from(&error);
+        if self.inner.method_not_allowed && error.is_method_not_allowed() {
+            problem = problem.with_status(StatusCode::MethodNotAllowed);
+        }
+
+        let mut response = if self.inner.dev_mode {
+            let explanation = self.explain(&request.method, request.uri.path());
+            Response::new().with_content(explanation.to_string(), mime::TEXT_PLAIN)
         } else {
-            None
+            if self.inner.suggestions && problem.status == StatusCode::NotFound {
+                problem.suggestions = self.suggest(&request.method, request.uri.path());
+            }
+            self.render_error(request, &problem)
+        };
+        if problem.status == StatusCode::MethodNotAllowed {
+            response.headers.set(header::Allow(problem.allowed_methods.clone()));
+        }
+        Err(FerrumError::new(error, Some(response.with_status(problem.status))))
+    }
+
+    /// Render the `problem` with the error renderer negotiated for the request, the response
+    /// is empty if there is no renderer.
+    fn render_error(&self, request: &Request, problem: &Problem) -> Response {
+        let renderers = &self.inner.error_renderers;
+        match problem::negotiate(request, renderers.iter().map(|(media_type, _)| media_type)) {
+            Some(index) => renderers[index].1.render(request, problem),
+            None => Response::new(),
         }
     }
+
+    /// Describe the request no route matches, with the variants of its path that would match.
+    ///
+    /// The variants take a scan of the routes each, so they are only looked for if the
+    /// observers, the dev mode or the error renderers can report them. They aren't looked for
+    /// either if the routes of the path passed the request, they would match the variants too.
+    fn no_route(&self, method: &Method, path: &str) -> NoRoute {
+        let mut error = NoRoute::new(method.clone(), path);
+        error.allowed_methods = self.allowed_methods(path);
+        let inner = &self.inner;
+        let is_reported = !inner.observers.is_empty() || inner.dev_mode || !inner.error_renderers.is_empty();
+        if !is_reported || error.allowed_methods.contains(method) {
+            return error;
+        }
+
+        let is_routed = |path: &str| self.method_recognizers(method).iter()
+            .any(|recognizer| recognizer.recognize(path).is_some());
+
+        let trailing_slash = if path.len() > 1 && path.ends_with('/') {
+            path[..path.len() - 1].to_string()
+        } else {
+            format!("{}/", path)
+        };
+        let case_variant = path.to_lowercase();
+
+        if is_routed(&trailing_slash) {
+            error.trailing_slash = Some(trailing_slash);
+        }
+        if case_variant != path && is_routed(&case_variant) {
+            error.case_variant = Some(case_variant);
+        }
+        error
+    }
+
+    /// Dispatch the request to the first route matching it, the routes of its method first. The
+    /// routes after one which handler passes (see `Pass`) are tried next.
+    fn handle_method(&self, request: &mut Request) -> Option<FerrumResult<Response>> {
+        let method = request.method.clone();
+        let path = request.uri.path().to_string();
+        for matched in self.matches(&method, &path) {
+            if let Some(error) = matched.invalid_param {
+                return Some(Err(error.into()));
+            }
+            match self.dispatch(request, matched) {
+                Err(ref error) if error.error.is::<Pass>() => {
+                    request.extensions.remove::<MatchedRoute>();
+                    request.extensions.remove::<Router>();
+                    request.extensions.remove::<TypedParams>();
+                },
+                result => return Some(result),
+            }
+        }
+        None
+    }
+
+    fn dispatch(&self, request: &mut Request, matched: RouteMatch) -> FerrumResult<Response> {
+        let route = matched.recognizer.and_then(|recognizer| recognizer.route.clone());
+        if let Some(ref route) = route {
+            request.extensions.insert::<MatchedRoute>(route.clone());
+        }
+        request.extensions.insert::<Router>(matched.params);
+        request.extensions.insert::<TypedParams>(matched.typed_params);
+        request.extensions.insert::<RouterInner>(self.inner.clone());
+
+        let route = match route {
+            Some(ref route) if self.inner.metrics.is_some() || !self.inner.observers.is_empty() => route,
+            _ => return matched.handler.handle(request),
+        };
+
+        for observer in self.inner.observers.iter() {
+            observer.on_match(request, route);
+        }
+        let start = Instant::now();
+        let result = matched.handler.handle(request);
+        if let Some(ref metrics) = self.inner.metrics {
+            let passed = result.as_ref().err().map(|error| error.error.is::<Pass>()).unwrap_or(false);
+            if !passed {
+                metrics.record(route, &request.method, result.is_err(), start.elapsed());
+            }
+        }
+        for observer in self.inner.observers.iter() {
+            observer.on_result(request, route, &result);
+        }
+        result
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Router::new()
+    }
 }
 
 impl Key for Router {
@@ -259,42 +828,67 @@ impl Key for RouterInner {
 
 impl Handler for Router {
     fn handle(&self, request: &mut Request) -> FerrumResult<Response> {
-        self.handle_method(request).unwrap_or_else(||
-            match request.method {
-                Method::Options => Ok(self.handle_options(request.uri.path())),
-                // For HEAD, fall back to GET. Hyper ensures no response body is written.
-                Method::Head => {
-                    request.method = Method::Get;
-                    self.handle_method(request).unwrap_or(
-                        Err(
-                            FerrumError::new(
-                                NoRoute,
-                                Some(Response::new()
-                                    .with_status(StatusCode::NotFound))
-                            )
-                        )
-                    )
-                }
-                _ => Err(
-                    FerrumError::new(
-                        NoRoute,
-                        Some(Response::new()
-                            .with_status(StatusCode::NotFound))
-                    )
-                )
-            }
-        )
+        match self.cors_policy(request.uri.path()) {
+            Some(policy) => self.handle_cors(request, policy),
+            None => self.handle_routes(request)
+        }
     }
 }
 
-/// The error thrown by router if there is no matching route,
-/// it is always accompanied by a NotFound response.
-#[derive(Debug, PartialEq, Eq)]
-pub struct NoRoute;
+/// The error thrown by router if there is no matching route, it is accompanied by a NotFound
+/// response, or a MethodNotAllowed one if enabled, see `Router::method_not_allowed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoRoute {
+    /// The method of the request.
+    pub method: Method,
+
+    /// The path of the request.
+    pub path: String,
+
+    /// The methods the path can be dispatched with, empty if no route matches the path.
+    pub allowed_methods: Vec<Method>,
+
+    /// The path with its trailing slash added or removed, if a route matches it. It is only
+    /// looked for if the router has observers, error renderers or the dev mode enabled.
+    pub trailing_slash: Option<String>,
+
+    /// The lowercase path, if a route matches it, looked for like `trailing_slash`.
+    pub case_variant: Option<String>,
+}
+
+impl NoRoute {
+    pub fn new<P: Into<String>>(method: Method, path: P) -> NoRoute {
+        NoRoute {
+            method,
+            path: path.into(),
+            allowed_methods: Vec::new(),
+            trailing_slash: None,
+            case_variant: None,
+        }
+    }
+
+    /// Check whether the path is routed for other methods only, the request method isn't
+    /// one of the allowed methods when all of its routes passed the request, see `Pass`.
+    pub fn is_method_not_allowed(&self) -> bool {
+        !self.allowed_methods.is_empty() && !self.allowed_methods.contains(&self.method)
+    }
+}
 
 impl fmt::Display for NoRoute {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str("No matching route found.")
+        write!(f, "No matching route found for {} {}.", self.method, self.path)?;
+        if !self.allowed_methods.is_empty() {
+            let allowed = self.allowed_methods.iter()
+                .map(|method| method.as_ref())
+                .collect::<Vec<_>>();
+            write!(f, " Allowed methods: {}.", allowed.join(", "))?;
+        }
+        if let Some(ref path) = self.trailing_slash {
+            write!(f, " Did you mean `{}`?", path)?;
+        } else if let Some(ref path) = self.case_variant {
+            write!(f, " Did you mean `{}`?", path)?;
+        }
+        Ok(())
     }
 }
 
@@ -302,5 +896,38 @@ impl Error for NoRoute {
     fn description(&self) -> &str { "No Route" }
 }
 
+/// The error a route handler returns to decline the request, the router then tries the next
+/// routes matching it: the routes after the handler one for the method, then the ones of any
+/// method. The request results in a `NoRoute` error if every matching route passes.
+///
+/// ```ignore
+/// fn article(request: &mut Request) -> FerrumResult<Response> {
+///     let article = match find_article(request.param("slug")?) {
+///         Some(article) => article,
+///         None => return Err(Pass.into()),
+///     };
+///     ...
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pass;
+
+impl fmt::Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("The handler passed the request to the next route.")
+    }
+}
+
+impl Error for Pass {
+    fn description(&self) -> &str { "Pass" }
+}
+
+/// A handler passing a request outside of a router results in a NotFound response.
+impl From<Pass> for FerrumError {
+    fn from(pass: Pass) -> FerrumError {
+        FerrumError::new(pass, Some(Response::new().with_status(StatusCode::NotFound)))
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,46 @@
+use ferrum::{Request, Response, FerrumResult, Method};
+
+use router::MatchedRoute;
+
+/// Callbacks on the routing decisions of a `Router`, e.g. for structured logs.
+///
+/// Every callback does nothing by default. An observer is set with `Router::observer` and
+/// is notified of the routes registered before it too.
+///
+/// ```ignore
+/// struct Log;
+///
+/// impl RouteObserver for Log {
+///     fn on_miss(&self, request: &Request, miss: &RouteMiss) {
+///         println!("{} {}: {:?}", request.method, request.uri, miss);
+///     }
+/// }
+///
+/// router.observer(Log);
+/// ```
+pub trait RouteObserver: Send + Sync + 'static {
+    /// A route is registered.
+    fn on_register(&self, _route: &MatchedRoute) {}
+
+    /// The request matched the route, its handler is called next.
+    fn on_match(&self, _request: &Request, _route: &MatchedRoute) {}
+
+    /// No route matched the request, it results in a `NoRoute` error.
+    fn on_miss(&self, _request: &Request, _miss: &RouteMiss) {}
+
+    /// The handler of the route returned the `result`.
+    fn on_result(&self, _request: &Request, _route: &MatchedRoute, _result: &FerrumResult<Response>) {}
+}
+
+/// Why no route matched a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteMiss {
+    /// No route matches the path.
+    NotFound,
+    /// Some routes match the path, with the other methods only, see
+    /// `NoRoute::is_method_not_allowed`.
+    MethodNotAllowed(Vec<Method>),
+    /// No route matches the path, but one matches its variant with or without a trailing
+    /// slash, or else in lower case, which the request may be redirected to, see `NoRoute`.
+    Redirectable(String),
+}
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use ferrum::{header, mime, Method, Request, Response, StatusCode};
+
+use router::{NoRoute, Suggestion};
+
+/// The media type of the RFC 7807 problem documents in JSON.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// The details of a routing error, rendered as a RFC 7807 problem document by default,
+/// see `Router::error_renderer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The `type` member, a URI identifying the kind of problem.
+    pub type_uri: String,
+    /// A short summary of the kind of problem.
+    pub title: String,
+    pub status: StatusCode,
+    /// An explanation specific to this occurrence of the problem.
+    pub detail: String,
+    /// The path of the request.
+    pub instance: String,
+    /// The methods the path can be dispatched with, an extension member if not empty.
+    pub allowed_methods: Vec<Method>,
+    /// The routes close to the path, an extension member if not empty, see
+    /// `Router::suggestions`.
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl<'a> From<&'a NoRoute> for Problem {
+    fn from(error: &'a NoRoute) -> Problem {
+        let status = StatusCode::NotFound;
+        Problem {
+            type_uri: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or("").to_string(),
+            status,
+            detail: error.to_string(),
+            instance: error.path.clone(),
+            allowed_methods: error.allowed_methods.clone(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl Problem {
+    /// Replace the status of the problem and its title by the reason of the status, e.g. for
+    /// a MethodNotAllowed response, see `Router::method_not_allowed`.
+    pub fn with_status(mut self, status: StatusCode) -> Problem {
+        self.title = status.canonical_reason().unwrap_or("").to_string();
+        self.status = status;
+        self
+    }
+
+    /// The problem document in JSON.
+    pub fn to_json(&self) -> String {
+        let mut body = format!(
+            "{{\"type\":\"{}\",\"title\":\"{}\",\"status\":{},\"detail\":\"{}\",\"instance\":\"{}\"",
+            escape_json(&self.type_uri), escape_json(&self.title), self.status.as_u16(),
+            escape_json(&self.detail), escape_json(&self.instance)
+        );
+        if !self.allowed_methods.is_empty() {
+            let allowed = self.allowed_methods.iter()
+                .map(|method| format!("\"{}\"", escape_json(method.as_ref())))
+                .collect::<Vec<_>>();
+            let _ = write!(body, ",\"allowed_methods\":[{}]", allowed.join(","));
+        }
+        if !self.suggestions.is_empty() {
+            body.push_str(",\"suggestions\":[");
+            for (index, suggestion) in self.suggestions.iter().enumerate() {
+                if index > 0 {
+                    body.push(',');
+                }
+                let route = &suggestion.route;
+                let _ = write!(
+                    body, "{{\"method\":\"{}\",\"route\":\"{}\"",
+                    escape_json(route.method_name()), escape_json(&route.glob)
+                );
+                if let Some(ref id) = route.id {
+                    let _ = write!(body, ",\"id\":\"{}\"", escape_json(id));
+                }
+                body.push('}');
+            }
+            body.push(']');
+        }
+        body.push('}');
+        body
+    }
+
+    /// The problem document in an HTML page.
+    pub fn to_html(&self) -> String {
+        let mut body = format!(
+            "<!DOCTYPE html>\n<html>\n<head><title>{} {}</title></head>\n<body>\n<h1>{}</h1>\n<p>{}</p>\n",
+            self.status.as_u16(), escape_html(&self.title), escape_html(&self.title), escape_html(&self.detail)
+        );
+        if !self.suggestions.is_empty() {
+            body.push_str("<p>Did you mean:</p>\n<ul>\n");
+            for suggestion in self.suggestions.iter() {
+                let route = &suggestion.route;
+                let _ = writeln!(body, "<li>{} {}</li>", escape_html(route.method_name()), escape_html(&route.glob));
+            }
+            body.push_str("</ul>\n");
+        }
+        body.push_str("</body>\n</html>\n");
+        body
+    }
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            ch if (ch as u32) < 0x20 => { let _ = write!(escaped, "\\u{:04x}", ch as u32); },
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders the body of the responses to the routing errors in a media type.
+///
+/// The router sets the status of the response. It is implemented for the functions taking
+/// the request and the problem:
+///
+/// ```ignore
+/// router.error_renderer(mime::TEXT_PLAIN, |_: &Request, problem: &Problem| {
+///     Response::new().with_content(problem.detail.clone(), mime::TEXT_PLAIN)
+/// });
+/// ```
+pub trait ErrorRenderer: Send + Sync + 'static {
+    fn render(&self, request: &Request, problem: &Problem) -> Response;
+}
+
+impl<F> ErrorRenderer for F
+    where F: Send + Sync + 'static + Fn(&Request, &Problem) -> Response
+{
+    fn render(&self, request: &Request, problem: &Problem) -> Response {
+        (*self)(request, problem)
+    }
+}
+
+/// Renders the problem documents in JSON, `application/problem+json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemJson;
+
+impl ErrorRenderer for ProblemJson {
+    fn render(&self, _: &Request, problem: &Problem) -> Response {
+        let media_type = PROBLEM_JSON.parse::<mime::Mime>().unwrap();
+        Response::new().with_content(problem.to_json(), media_type)
+    }
+}
+
+/// Renders the problem documents in HTML pages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemHtml;
+
+impl ErrorRenderer for ProblemHtml {
+    fn render(&self, _: &Request, problem: &Problem) -> Response {
+        Response::new().with_content(problem.to_html(), mime::TEXT_HTML_UTF_8)
+    }
+}
+
+/// The index of the media type the request accepts with the highest quality, the first one
+/// on a tie. It is the first one if the request has no `Accept` header or accepts none of them.
+///
+/// The quality of a media type is the one of the most specific range covering it.
+pub fn negotiate<'a, I>(request: &Request, media_types: I) -> Option<usize>
+    where I: IntoIterator<Item = &'a mime::Mime>
+{
+    let accept = request.headers.get::<header::Accept>();
+    let mut count = 0;
+    let mut best = None;
+    for (index, media_type) in media_types.into_iter().enumerate() {
+        count += 1;
+        let accept = match accept {
+            Some(accept) => accept,
+            None => continue,
+        };
+        let quality = accept.iter()
+            .filter_map(|item| specificity(&item.item, media_type).map(|specificity| (specificity, item.quality)))
+            .max_by_key(|&(specificity, _)| specificity)
+            .map(|(_, quality)| quality)
+            .unwrap_or(header::q(0));
+        if quality > header::q(0) && best.map(|(_, best)| quality > best).unwrap_or(true) {
+            best = Some((index, quality));
+        }
+    }
+    best.map(|(index, _)| index).or(if count > 0 { Some(0) } else { None })
+}
+
+/// How specific the `range` of an `Accept` header covering the `media_type` is, if it covers
+/// it. `application/json` covers the `+json` types too, less specifically than themselves.
+fn specificity(range: &mime::Mime, media_type: &mime::Mime) -> Option<u8> {
+    if range.type_() == mime::STAR {
+        Some(0)
+    } else if range.type_() != media_type.type_() {
+        None
+    } else if range.subtype() == media_type.subtype() {
+        Some(3)
+    } else if range.subtype() == mime::JSON && media_type.suffix() == Some(mime::JSON) {
+        Some(2)
+    } else if range.subtype() == mime::STAR {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use ferrum::request::HyperRequest;
+    use router::MatchedRoute;
+
+    #[test]
+    fn render() {
+        let mut error = NoRoute::new(Method::Delete, "/users/\"1\"");
+        error.allowed_methods = vec![Method::Get, Method::Head];
+        let problem = Problem::from(&error);
+
+        assert_eq!(
+            concat!(
+                r#"{"type":"about:blank","title":"Not Found","status":404,"#,
+                r#""detail":"No matching route found for DELETE /users/\"1\". Allowed methods: GET, HEAD.","#,
+                r#""instance":"/users/\"1\"","allowed_methods":["GET","HEAD"]}"#
+            ),
+            problem.to_json()
+        );
+        assert_eq!(
+            concat!(
+                "<!DOCTYPE html>\n<html>\n<head><title>404 Not Found</title></head>\n<body>\n<h1>Not Found</h1>\n",
+                "<p>No matching route found for DELETE /users/&quot;1&quot;. Allowed methods: GET, HEAD.</p>\n",
+                "</body>\n</html>\n"
+            ),
+            problem.to_html()
+        );
+
+        let problem = problem.with_status(StatusCode::MethodNotAllowed);
+        assert!(problem.to_json().starts_with(r#"{"type":"about:blank","title":"Method Not Allowed","status":405,"#));
+        assert!(problem.to_html().contains("<title>405 Method Not Allowed</title>"));
+    }
+
+    #[test]
+    fn render_suggestions() {
+        let mut problem = Problem::from(&NoRoute::new(Method::Get, "/usres/<1>"));
+        problem.suggestions = vec![Suggestion {
+            route: Arc::new(MatchedRoute {
+                id: Some("user".into()),
+                glob: "/users/{id}".to_string(),
+                method: None,
+                index: 0,
+            }),
+            distance: 2,
+        }];
+
+        assert_eq!(
+            concat!(
+                r#"{"type":"about:blank","title":"Not Found","status":404,"#,
+                r#""detail":"No matching route found for GET /usres/<1>.","instance":"/usres/<1>","#,
+                r#""suggestions":[{"method":"any","route":"/users/{id}","id":"user"}]}"#
+            ),
+            problem.to_json()
+        );
+        assert!(problem.to_html().contains(
+            "<p>No matching route found for GET /usres/&lt;1&gt;.</p>\n<p>Did you mean:</p>\n<ul>\n<li>any /users/{id}</li>\n</ul>\n"
+        ));
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(r#"a\"b\\c\u000a"#, escape_json("a\"b\\c\n"));
+        assert_eq!("&lt;a href=&quot;x&quot;&gt;&amp;", escape_html("<a href=\"x\">&"));
+    }
+
+    #[test]
+    fn negotiation() {
+        let media_types: [mime::Mime; 3] = [PROBLEM_JSON.parse().unwrap(), mime::TEXT_HTML, mime::TEXT_PLAIN];
+        let negotiate = |accept: Option<&str>| {
+            let mut request = Request::new(HyperRequest::new(Method::Get, "http://localhost/".parse().unwrap()));
+            if let Some(accept) = accept {
+                request.headers.set_raw("Accept", accept.to_string());
+            }
+            super::negotiate(&request, media_types.iter())
+        };
+
+        assert_eq!(Some(0), negotiate(None));
+        assert_eq!(Some(0), negotiate(Some("*/*")));
+        assert_eq!(Some(0), negotiate(Some("application/json")));
+        assert_eq!(Some(0), negotiate(Some("image/png")));
+        assert_eq!(Some(1), negotiate(Some("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")));
+        assert_eq!(Some(2), negotiate(Some("text/plain, text/*;q=0.5")));
+        assert_eq!(Some(1), negotiate(Some("text/*, application/json;q=0")));
+        assert_eq!(Some(1), negotiate(Some("*/*, application/problem+json;q=0")));
+        assert_eq!(Some(0), negotiate(Some("text/html;q=0.5, application/*;q=0.1, application/json")));
+        assert_eq!(None, super::negotiate(
+            &Request::new(HyperRequest::new(Method::Get, "http://localhost/".parse().unwrap())), &[]
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use std::cmp;
+use std::sync::Arc;
+
+use recognizer::{GlobNode, Recognizer};
+use router::MatchedRoute;
+
+/// The most suggestions listed in a routing error.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The most variants of a glob with optional sections compared with a path.
+const MAX_VARIANTS: usize = 16;
+
+/// A registered route close to a path no route matches, see `Router::suggestions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub route: Arc<MatchedRoute>,
+    /// The edit distance between the path and the literal segments of the route glob.
+    pub distance: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// A segment with a param, which matches any path segment.
+    Param,
+    /// A `{*name}` param, which matches the rest of the path.
+    Tail,
+}
+
+/// The routes of the `recognizers` close to the `path`, the closest first.
+///
+/// The distance to a route is the edit distance between the path segments and the literal
+/// segments of its glob (any path segment matches a segment with a param). The routes with
+/// the raw regex syntax aren't suggested.
+pub fn suggest<'a, I>(recognizers: I, path: &str) -> Vec<Suggestion>
+    where I: Iterator<Item = &'a Arc<Recognizer>>
+{
+    let path_segments = path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+    let max_distance = cmp::max(2, path_segments.iter().map(|segment| segment.len()).sum::<usize>() / 3);
+
+    let mut suggestions = recognizers
+        .filter(|recognizer| !recognizer.glob.raw_regex)
+        .filter_map(|recognizer| {
+            let route = recognizer.route.clone()?;
+            let distance = variants(&recognizer.glob.nodes).iter()
+                .map(|variant| distance(&path_segments, &segments(variant)))
+                .min()?;
+            Some(Suggestion { route, distance })
+        })
+        .filter(|suggestion| suggestion.distance <= max_distance)
+        .collect::<Vec<_>>();
+    suggestions.sort_by_key(|suggestion| (suggestion.distance, suggestion.route.index));
+    suggestions.dedup_by(|a, b| a.route.index == b.route.index);
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// The glob text of the `nodes` with each optional section present or absent, the params
+/// replaced by `\u{0}` and the tail params by `\u{1}`.
+fn variants(nodes: &[GlobNode]) -> Vec<String> {
+    let mut variants = vec![String::new()];
+    for node in nodes.iter() {
+        match *node {
+            GlobNode::Literal(ref text) => variants.iter_mut().for_each(|variant| variant.push_str(text)),
+            GlobNode::Param(_) => variants.iter_mut().for_each(|variant| variant.push('\u{0}')),
+            GlobNode::Tail(_) => variants.iter_mut().for_each(|variant| variant.push('\u{1}')),
+            GlobNode::Optional(ref nodes) => {
+                let optional_variants = self::variants(nodes);
+                let mut extended = variants.clone();
+                for variant in variants.iter() {
+                    for optional in optional_variants.iter() {
+                        extended.push(format!("{}{}", variant, optional));
+                    }
+                }
+                extended.truncate(MAX_VARIANTS);
+                variants = extended;
+            },
+        }
+    }
+    variants
+}
+
+fn segments(variant: &str) -> Vec<Segment> {
+    variant.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| if segment.contains('\u{1}') {
+            Segment::Tail
+        } else if segment.contains('\u{0}') {
+            Segment::Param
+        } else {
+            Segment::Literal(segment.to_string())
+        })
+        .collect()
+}
+
+/// The edit distance between the path segments and the glob segments, a segment
+/// costs its length plus one (for the slash) to add or remove.
+fn distance(path: &[&str], glob: &[Segment]) -> usize {
+    let mut costs = vec![vec![usize::MAX; glob.len() + 1]; path.len() + 1];
+    costs[0][0] = 0;
+    for i in 0..=path.len() {
+        for j in 0..=glob.len() {
+            let cost = costs[i][j];
+            if cost == usize::MAX {
+                continue;
+            }
+            if i < path.len() {
+                let removed = cost + path[i].chars().count() + 1;
+                costs[i + 1][j] = cmp::min(costs[i + 1][j], removed);
+            }
+            if j < glob.len() {
+                let added = cost + match glob[j] {
+                    Segment::Literal(ref literal) => literal.chars().count() + 1,
+                    Segment::Param => 1,
+                    Segment::Tail => 0,
+                };
+                costs[i][j + 1] = cmp::min(costs[i][j + 1], added);
+            }
+            if i < path.len() && j < glob.len() {
+                let (replaced, next_j) = match glob[j] {
+                    Segment::Literal(ref literal) => (cost + levenshtein(path[i], literal), j + 1),
+                    Segment::Param => (cost, j + 1),
+                    // The tail stays to match the next path segments too.
+                    Segment::Tail => (cost, j),
+                };
+                costs[i + 1][next_j] = cmp::min(costs[i + 1][next_j], replaced);
+            }
+        }
+    }
+    costs[path.len()][glob.len()]
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replaced = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = cmp::min(replaced, cmp::min(previous[j + 1], current[j]) + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let path = |path: &'static str| path.split('/').filter(|segment| !segment.is_empty()).collect::<Vec<_>>();
+        let glob = |glob: &str| segments(glob);
+
+        assert_eq!(2, levenshtein("usres", "users"));
+        assert_eq!(3, levenshtein("", "abc"));
+        assert_eq!(0, distance(&path("/users/42"), &glob("/users/\u{0}")));
+        assert_eq!(2, distance(&path("/usres/42"), &glob("/users/\u{0}")));
+        assert_eq!(6, distance(&path("/users/42"), &glob("/users/\u{0}/posts")));
+        assert_eq!(1, distance(&path("/file/a/b/c"), &glob("/files/\u{1}")));
+        assert_eq!(vec!["/a", "/a/\u{0}", "/a/\u{0}/\u{1}"], variants(&[
+            GlobNode::Literal("/a".to_string()),
+            GlobNode::Optional(vec![
+                GlobNode::Literal("/".to_string()),
+                GlobNode::Param(::recognizer::ParamNode {
+                    name: Some("b".to_string()),
+                    kind: ::recognizer::ParamKind::Default,
+                    span: 3..6,
+                }),
+                GlobNode::Optional(vec![
+                    GlobNode::Literal("/".to_string()),
+                    GlobNode::Tail(::recognizer::TailNode { name: "c".to_string(), span: 8..12 }),
+                ]),
+            ]),
+        ]));
+    }
+}
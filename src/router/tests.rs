@@ -1,7 +1,10 @@
 use super::*;
 
-use ferrum::{header, mime, Method, Request, Response};
-use recognizer::{DefaultStore, DefaultStoreBuild, Type};
+use ferrum::{header, mime, Method, Request, Response, FerrumResult};
+use ferrum::request::HyperRequest;
+use recognizer::{DefaultStore, DefaultStoreBuild, Glob, ParamError, Params, Segments, Store, Type, TypedStore, TypedValue};
+use recognizer::ValidationFailure;
+use uri_for::UriFor;
 
 #[test]
 fn test_handle_options_post() {
@@ -12,7 +15,7 @@ fn test_handle_options_post() {
 
     let resp = router.handle_options("/");
     let headers = resp.headers.get::<header::Allow>().unwrap();
-    let expected = header::Allow(vec![Method::Post]);
+    let expected = header::Allow(vec![Method::Post, Method::Options]);
 
     assert_eq!(&expected, headers);
 }
@@ -26,11 +29,75 @@ fn test_handle_options_get_head() {
 
     let resp = router.handle_options("/");
     let headers = resp.headers.get::<header::Allow>().unwrap();
-    let expected = header::Allow(vec![Method::Get, Method::Head]);
+    let expected = header::Allow(vec![Method::Get, Method::Head, Method::Options]);
 
     assert_eq!(&expected, headers);
 }
 
+#[test]
+fn test_handle_options_any() {
+    let mut router = Router::new();
+    router.post("/", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.any("/any", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.route(Method::Extension("PURGE".to_string()), "/any", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let resp = router.handle_options("/any");
+    let headers = resp.headers.get::<header::Allow>().unwrap();
+    let expected = header::Allow(vec![
+        Method::Get,
+        Method::Post,
+        Method::Put,
+        Method::Delete,
+        Method::Head,
+        Method::Patch,
+        Method::Extension("PURGE".to_string()),
+        Method::Options,
+    ]);
+
+    assert_eq!(&expected, headers);
+
+    let resp = router.handle_options("/none");
+    let headers = resp.headers.get::<header::Allow>().unwrap();
+
+    assert_eq!(&header::Allow(vec![]), headers);
+}
+
+#[test]
+fn test_handle_options_explicit() {
+    let mut router = Router::new();
+    router.get("/", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.options("/", |_: &mut Request| {
+        Ok(Response::new().with_content("custom", mime::TEXT_PLAIN))
+    }, None);
+    router.get("/auto", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let expected = header::Allow(vec![Method::Get, Method::Head, Method::Options]);
+    assert_eq!(expected.0, router.allowed_methods("/"));
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Options, "http://localhost/".parse().unwrap())
+    );
+    let resp = router.handle(&mut request).unwrap();
+    assert!(resp.headers.get::<header::Allow>().is_none());
+    assert_eq!(Some(&header::ContentLength(6)), resp.headers.get());
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Options, "http://localhost/auto".parse().unwrap())
+    );
+    let resp = router.handle(&mut request).unwrap();
+    assert_eq!(Some(&expected), resp.headers.get());
+}
+
 #[test]
 fn test_handle_any_ok() {
     let mut router = Router::new();
@@ -93,7 +160,7 @@ fn test_same_route_id() {
 #[test]
 fn test_wildcard_regression() {
     let mut router = Router::new();
-    router.options(".*", |_: &mut Request| {
+    router.options(Glob::from(".*").raw_regex(), |_: &mut Request| {
         Ok(Response::new().with_content("", mime::TEXT_PLAIN))
     }, None);
     router.put("/upload/{filename}", |_: &mut Request| {
@@ -110,7 +177,7 @@ fn test_glob_types() {
     let mut router = Router::new();
     let types = DefaultStore::with_default_types();
 
-    router.get(".*", |_: &mut Request| {
+    router.get(Glob::from(".*").raw_regex(), |_: &mut Request| {
         Ok(Response::new().with_content("", mime::TEXT_PLAIN))
     }, None);
     router.post("/upload/{filename}", |_: &mut Request| {
@@ -127,12 +194,297 @@ fn test_glob_types() {
     assert!(router.recognize(&Method::Post, "/send/no").is_none());
 }
 
+#[test]
+fn test_params_named_like_types() {
+    let mut router = Router::new();
+    for glob in &["/blog/{slug}", "/x/{hex}", "/cal/{date}", "/u/{uuid}", "/n/{int}"] {
+        router.get(*glob, |_: &mut Request| Ok(Response::new()), None);
+    }
+    router.get("/typed/{s:slug}", |_: &mut Request| Ok(Response::new()), None);
+
+    for path in &["/blog/Hello_World", "/x/zzz", "/cal/today", "/u/me", "/n/one"] {
+        let route_match = router.recognize(&Method::Get, path).unwrap();
+        assert_eq!(0, route_match.typed_params.len(), "{}", path);
+    }
+    assert!(router.recognize(&Method::Get, "/typed/hello-world").is_some());
+    assert!(router.recognize(&Method::Get, "/typed/Hello_World").is_none());
+
+    // A redefined type still applies to the params with its name, as any custom type does.
+    router.glob_type(Type::SLUG_NAME, "[a-z]+");
+    router.get("/tags/{slug}", |_: &mut Request| Ok(Response::new()), None);
+    assert!(router.recognize(&Method::Get, "/tags/rust").is_some());
+    assert!(router.recognize(&Method::Get, "/tags/Rust").is_none());
+}
+
+#[test]
+fn test_literal_globs() {
+    let mut router = Router::new();
+    router.get("/feed.json", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.get("/c++/{page}", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+    router.get(Glob::from("/feed.(xml|rss)").raw_regex(), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    assert!(router.recognize(&Method::Get, "/feed.json").is_some());
+    assert!(router.recognize(&Method::Get, "/feedXjson").is_none());
+    assert!(router.recognize(&Method::Get, "/c++/intro").is_some());
+    assert!(router.recognize(&Method::Get, "/c/intro").is_none());
+    assert!(router.recognize(&Method::Get, "/feed.rss").is_some());
+    assert!(router.recognize(&Method::Get, "/feedXxml").is_some());
+}
+
+#[test]
+fn test_optional_sections() {
+    let mut router = Router::new();
+    router.get("/archive/{year:number}[/{month:number}[/{day:number}]]", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, Id::some("archive"));
+    router.get(Glob::from("/list[/page/{page:number}]").with_default("page", "1"), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let params = |path: &str| {
+        let route_match = router.recognize(&Method::Get, path).unwrap();
+        let page = route_match.typed_params.get::<u64>("page").cloned();
+        (route_match.params.into_iter().collect::<Vec<_>>(), page)
+    };
+    let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+
+    assert_eq!((vec![pair("year", "2018")], None), params("/archive/2018"));
+    assert_eq!((vec![pair("year", "2018"), pair("month", "02")], None), params("/archive/2018/02/"));
+    assert_eq!(
+        (vec![pair("year", "2018"), pair("month", "02"), pair("day", "28")], None),
+        params("/archive/2018/02/28")
+    );
+    assert!(router.recognize(&Method::Get, "/archive/2018/02/28/1").is_none());
+    assert!(router.recognize(&Method::Get, "/archive").is_none());
+
+    assert_eq!((vec![pair("page", "1")], Some(1)), params("/list"));
+    assert_eq!((vec![pair("page", "3")], Some(3)), params("/list/page/3"));
+    assert!(router.recognize(&Method::Get, "/list/page").is_none());
+
+    let (_, ref recognizer) = router.inner.route_ids["archive"];
+    let uri: ::ferrum::Uri = "http://localhost/".parse().unwrap();
+    let mut params = Params::new();
+    params.insert("year".to_string(), "2018".to_string());
+    assert_eq!("http://localhost/archive/2018", uri.generate(Some(&recognizer.glob), recognizer, params.clone()).unwrap());
+    params.insert("month".to_string(), "02".to_string());
+    assert_eq!("http://localhost/archive/2018/02", uri.generate(Some(&recognizer.glob), recognizer, params).unwrap());
+}
+
+#[test]
+fn test_tail_segments() {
+    let mut router = Router::new();
+    router.get("/files/{*path}", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, Id::some("files"));
+    router.get(Glob::from("/raw/{*path}").allow_parent_segments(), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let segments = |path: &str| {
+        router.recognize(&Method::Get, path)
+            .map(|route_match| route_match.typed_params.get::<Segments>("path").unwrap().to_vec())
+    };
+
+    assert_eq!(Some(vec!["a".to_string(), "b c.txt".to_string()]), segments("/files/a/b%20c.txt"));
+    assert_eq!(Some(Vec::<String>::new()), segments("/files/"));
+    assert_eq!(None, segments("/files/a/../../etc/passwd"));
+    assert_eq!(None, segments("/files/%2E%2E/etc/passwd"));
+    assert_eq!(Some(vec!["..".to_string(), "b".to_string()]), segments("/raw/../b"));
+    assert_eq!(None, segments("/files/%2e%2e%2f%2e%2e%2fetc/passwd"));
+    assert_eq!(None, segments("/files/..%5c..%5cwin.ini"));
+    assert_eq!(None, segments("/raw/%2e%2e%2fetc/passwd"));
+    assert_eq!(None, segments("/files/a%00.txt"));
+
+    let (_, ref recognizer) = router.inner.route_ids["files"];
+    let uri: ::ferrum::Uri = "http://localhost/".parse().unwrap();
+    let mut params = Params::new();
+    params.insert("path".to_string(), Segments::from(vec!["a b", "c?d"]).into());
+    let uri = uri.generate(Some(&recognizer.glob), recognizer, params).unwrap();
+    assert_eq!("http://localhost/files/a%20b/c%3Fd", uri);
+    assert_eq!(Some(vec!["a b".to_string(), "c?d".to_string()]), segments(uri.path()));
+}
+
+#[test]
+#[should_panic(expected = "default value for the unknown param `page`")]
+fn test_unknown_default() {
+    let mut router = Router::new();
+    router.get(Glob::from("/list").with_default("page", "1"), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+}
+
+#[test]
+fn test_router_types() {
+    let handler = |_: &mut Request| Ok(Response::new().with_content("", mime::TEXT_PLAIN));
+
+    let mut router = Router::new();
+    router.get("/before/{id}", handler, None);
+    router.glob_type("id", "[1-9][0-9]*");
+    router.glob_type(Type::NUMBER_NAME, "[0-7]+");
+    router.get("/users/{id}", handler, None);
+    router.get("/items/{item:id}/{count:number}", handler, None);
+
+    let mut types = Store::<&str, &str>::default();
+    types.insert("id", "[a-z]+");
+    router.get(("/posts/{id}/{page:number}", &types), handler, None);
+
+    let mut typed = TypedStore::<String, String>::default();
+    typed.insert("flag".to_string(), "yes|no".to_string());
+    typed.converters.insert("flag", |value| Some(Box::new(value == "yes") as TypedValue));
+    router.glob_types(&typed);
+    router.get("/flags/{flag}", handler, None);
+
+    assert!(router.recognize(&Method::Get, "/before/abc").is_some());
+    assert!(router.recognize(&Method::Get, "/users/12").is_some());
+    assert!(router.recognize(&Method::Get, "/users/012").is_none());
+    assert!(router.recognize(&Method::Get, "/users/abc").is_none());
+    assert!(router.recognize(&Method::Get, "/items/12/7").is_some());
+    assert!(router.recognize(&Method::Get, "/items/12/8").is_none());
+    assert!(router.recognize(&Method::Get, "/posts/abc/7").is_some());
+    assert!(router.recognize(&Method::Get, "/posts/12/7").is_none());
+
+    let route_match = router.recognize(&Method::Get, "/flags/yes").unwrap();
+    assert_eq!(Some(&true), route_match.typed_params.get::<bool>("flag"));
+    assert!(router.recognize(&Method::Get, "/flags/maybe").is_none());
+    assert_eq!(Some(&"[1-9][0-9]*".to_string()), router.types().patterns.get("id"));
+}
+
+#[test]
+fn test_composed_types() {
+    let mut router = Router::new();
+    router.glob_type("ymd", "{year:number}-{month:number}-{day:number}");
+    router.get("/archive/{date:ymd}", |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let route_match = router.recognize(&Method::Get, "/archive/2018-02-28").unwrap();
+    let params = route_match.params.iter().collect::<Vec<_>>();
+    assert_eq!(vec![
+        ("date", "2018-02-28"),
+        ("date.year", "2018"),
+        ("date.month", "02"),
+        ("date.day", "28"),
+    ], params);
+    assert_eq!(Some(&2u64), route_match.typed_params.get::<u64>("date.month"));
+    assert!(router.recognize(&Method::Get, "/archive/2018-02").is_none());
+}
+
+#[test]
+fn test_regex_braces_in_types() {
+    let mut router = Router::new();
+    router.glob_type("word", r"\p{L}+");
+    router.glob_type("ymd", r"{year:[0-9]{4}}-[0-9]{2}-[0-9]{2}");
+    router.get(r"/n/{name:\p{L}+}", |_: &mut Request| Ok(Response::new()), None);
+    router.get(r"/w/{name:word}/{code:\P{L}{2,3}}", |_: &mut Request| Ok(Response::new()), None);
+    router.get(r"/e/{emoji:\x{1F600}}", |_: &mut Request| Ok(Response::new()), None);
+    router.get("/d/{date:ymd}", |_: &mut Request| Ok(Response::new()), None);
+
+    assert!(router.recognize(&Method::Get, "/n/Привет").is_some());
+    assert!(router.recognize(&Method::Get, "/n/42").is_none());
+    assert!(router.recognize(&Method::Get, "/w/été/42").is_some());
+    assert!(router.recognize(&Method::Get, "/w/été/4").is_none());
+    assert!(router.recognize(&Method::Get, "/e/\u{1F600}").is_some());
+    let route_match = router.recognize(&Method::Get, "/d/2018-02-28").unwrap();
+    assert_eq!(Some("2018"), route_match.params.get("date.year"));
+}
+
+#[test]
+fn test_ordered_params() {
+    let mut router = Router::new();
+    router.get(Glob::from("/{user}/posts/{id}[/{title}]").with_default("title", "untitled"), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+
+    let route_match = router.recognize(&Method::Get, "/bob%20smith/posts/42").unwrap();
+    let params = route_match.params.entries()
+        .map(|param| (param.name, param.raw, param.decoded))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![
+        ("user", "bob%20smith", "bob smith"),
+        ("id", "42", "42"),
+        ("title", "untitled", "untitled"),
+    ], params);
+    assert_eq!(Some("bob smith"), route_match.params.get("user"));
+    assert_eq!(Some("bob%20smith"), route_match.params.get_raw("user"));
+}
+
+#[test]
+fn test_validators() {
+    let handler = |_: &mut Request| Ok(Response::new().with_content("", mime::TEXT_PLAIN));
+    let in_range = |value: &str| value.parse::<u32>().map(|value| (1..=10000).contains(&value)).unwrap_or(false);
+
+    let mut router = Router::new();
+    router.type_validator("even", |value| value.parse::<u64>().map(|value| value % 2 == 0).unwrap_or(false));
+    router.glob_type("even", "[0-9]+");
+    router.get("/even/{n:even}", handler, None);
+    router.get(Glob::from("/items/{id:number}").with_validator("id", in_range), handler, None);
+    router.get("/items/{name}", handler, None);
+    router.get(
+        Glob::from("/strict/{id:number}")
+            .with_validator("id", in_range)
+            .on_validation_failure(ValidationFailure::BadRequest),
+        handler,
+        None
+    );
+
+    assert!(router.recognize(&Method::Get, "/even/4").is_some());
+    assert!(router.recognize(&Method::Get, "/even/5").is_none());
+
+    let route_match = router.recognize(&Method::Get, "/items/10000").unwrap();
+    assert!(route_match.params.contains_key("id"));
+    let route_match = router.recognize(&Method::Get, "/items/10001").unwrap();
+    assert_eq!(Some("10001"), route_match.params.get("name"));
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/strict/0".parse().unwrap())
+    );
+    let error = router.handle(&mut request).unwrap_err();
+    assert!(error.error.is::<ParamError>());
+    assert_eq!(Some(StatusCode::BadRequest), error.response.map(|response| response.status));
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/strict/10".parse().unwrap())
+    );
+    assert!(router.handle(&mut request).is_ok());
+
+    let mut router = Router::new();
+    router.get(
+        Glob::from("/users/{name}")
+            .with_validator("name", |name| name.chars().all(char::is_alphabetic))
+            .on_validation_failure(ValidationFailure::BadRequest),
+        handler,
+        None
+    );
+    assert!(router.recognize(&Method::Get, "/users/j%C3%B6rg").unwrap().invalid_param.is_none());
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/users/j%C3%B6rg%21".parse().unwrap())
+    );
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!("Invalid route param `name` = `jörg!`: rejected by the validator.", error.to_string());
+}
+
+#[test]
+#[should_panic(expected = "validator for the unknown param `id`")]
+fn test_unknown_validator() {
+    let mut router = Router::new();
+    router.get(Glob::from("/list").with_validator("id", |_| true), |_: &mut Request| {
+        Ok(Response::new().with_content("", mime::TEXT_PLAIN))
+    }, None);
+}
+
 #[test]
 fn test_route_ids() {
     let mut router = Router::new();
     let types = DefaultStore::with_default_types();
 
-    router.get(".*", |_: &mut Request| {
+    router.get(Glob::from(".*").raw_regex(), |_: &mut Request| {
         Ok(Response::new().with_content("", mime::TEXT_PLAIN))
     }, Id::some("id1"));
     router.post("/upload/{filename}", |_: &mut Request| {
@@ -157,3 +509,478 @@ fn test_route_ids() {
     assert_eq!("/send/{id:number}", path);
     assert_eq!(&format!("^/send/(?P<id>{})/?$", Type::NUMBER_PATTERN), recognizer.glob_regex.as_str());
 }
+
+#[test]
+fn test_matched_route() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.get("/users/{id}", handler, Id::some("user"));
+    router.any("/files/{*path}", handler, None);
+    router.post("/users/{id}", handler, Id::some("user"));
+
+    let route = |method: Method, uri: &str| {
+        let mut request = Request::new(HyperRequest::new(method, uri.parse().unwrap()));
+        router.handle(&mut request).unwrap();
+        request.extensions.get::<MatchedRoute>().cloned().unwrap()
+    };
+
+    let user = route(Method::Get, "http://localhost/users/42");
+    assert_eq!(MatchedRoute {
+        id: Id::some("user"),
+        glob: "/users/{id}".to_string(),
+        method: Some(Method::Get),
+        index: 0,
+    }, *user);
+    assert_eq!("GET", user.method_name());
+
+    let user = route(Method::Post, "http://localhost/users/42");
+    assert_eq!((Some(Method::Post), 2), (user.method.clone(), user.index));
+
+    let file = route(Method::Delete, "http://localhost/files/a/b");
+    assert_eq!((None, 1, "any"), (file.method.clone(), file.index, file.method_name()));
+    assert_eq!("/files/{*path}", file.glob);
+
+    let head = route(Method::Head, "http://localhost/users/42");
+    assert_eq!(Some(Method::Get), head.method);
+}
+
+#[test]
+fn test_typed_params() {
+    let mut router = Router::new();
+    router.get("/users/{id:number}", |request: &mut Request| {
+        let id = *request.extensions.get::<TypedParams>().unwrap().get::<u64>("id").unwrap();
+        Ok(Response::new().with_content(format!("{}", id + 1), mime::TEXT_PLAIN))
+    }, None);
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/users/41".parse().unwrap())
+    );
+    let resp = router.handle(&mut request).unwrap();
+    assert_eq!(Some(&header::ContentLength(2)), resp.headers.get());
+
+    let mut request = Request::new(
+        HyperRequest::new(Method::Get, "http://localhost/users/99999999999999999999".parse().unwrap())
+    );
+    let error = router.handle(&mut request).unwrap_err();
+    assert!(error.error.is::<ParamError>());
+    assert_eq!(StatusCode::BadRequest, error.response.unwrap().status);
+}
+
+#[test]
+fn test_redefined_typed_params() {
+    let mut router = Router::new();
+    router.glob_type(Type::DATE_NAME, "[0-9]{8}");
+    router.glob_type(Type::NUMBER_NAME, r"[0-9]+(?:\.[0-9]+)?");
+    router.get("/d/{d:date}", |_: &mut Request| Ok(Response::new()), None);
+    router.get("/price/{price:number}", |_: &mut Request| Ok(Response::new()), None);
+
+    let route_match = router.recognize(&Method::Get, "/d/20180228").unwrap();
+    assert_eq!(Some("20180228"), route_match.params.get("d"));
+    assert!(!route_match.typed_params.contains("d"));
+    let route_match = router.recognize(&Method::Get, "/price/1.5").unwrap();
+    assert_eq!(Some("1.5"), route_match.params.get("price"));
+    assert!(!route_match.typed_params.contains("price"));
+
+    let mut types = TypedStore::<String, String>::with_default_types();
+    types.insert(Type::NUMBER_NAME.to_string(), r"[0-9]+(?:\.[0-9]+)?".to_string());
+    types.converters.insert_from_str::<f64>(Type::NUMBER_NAME);
+    router.get(("/amount/{amount:number}", &types), |_: &mut Request| Ok(Response::new()), None);
+    let route_match = router.recognize(&Method::Get, "/amount/1.5").unwrap();
+    assert_eq!(Some(&1.5f64), route_match.typed_params.get::<f64>("amount"));
+}
+
+#[test]
+fn test_observer() {
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl RouteObserver for Recorder {
+        fn on_register(&self, route: &MatchedRoute) {
+            self.0.lock().unwrap().push(format!("register {} {}", route.method_name(), route.glob));
+        }
+
+        fn on_match(&self, request: &Request, route: &MatchedRoute) {
+            self.0.lock().unwrap().push(format!("match {} {}", request.method, route.glob));
+        }
+
+        fn on_miss(&self, request: &Request, miss: &RouteMiss) {
+            self.0.lock().unwrap().push(format!("miss {} {:?}", request.method, miss));
+        }
+
+        fn on_result(&self, _: &Request, route: &MatchedRoute, result: &FerrumResult<Response>) {
+            self.0.lock().unwrap().push(format!("result {} {}", route.glob, result.is_ok()));
+        }
+    }
+
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+    fn pass(_: &mut Request) -> FerrumResult<Response> {
+        Err(Pass.into())
+    }
+
+    let recorder = Recorder::default();
+    let mut router = Router::new();
+    router.get("/users/{id}", handler, None);
+    router.observer(recorder.clone());
+    router.any("/any", handler, None);
+    router.get("/drafts", pass, None);
+
+    let handle = |method: Method, uri: &str| {
+        let mut request = Request::new(HyperRequest::new(method, uri.parse().unwrap()));
+        router.handle(&mut request).is_ok()
+    };
+    assert!(handle(Method::Get, "http://localhost/users/1"));
+    assert!(!handle(Method::Post, "http://localhost/users/1"));
+    assert!(!handle(Method::Get, "http://localhost/missing"));
+    assert!(!handle(Method::Get, "http://localhost/Users/1"));
+    assert!(!handle(Method::Get, "http://localhost/drafts"));
+
+    assert_eq!(vec![
+        "register GET /users/{id}",
+        "register any /any",
+        "register GET /drafts",
+        "match GET /users/{id}",
+        "result /users/{id} true",
+        "miss POST MethodNotAllowed([Get, Head, Options])",
+        "miss GET NotFound",
+        "miss GET Redirectable(\"/users/1\")",
+        "match GET /drafts",
+        "result /drafts false",
+        "miss GET NotFound",
+    ], *recorder.0.lock().unwrap());
+}
+
+#[test]
+fn test_explain() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.get("/users/{id:number}/posts", handler, Id::some("posts"));
+    router.get(Glob::from("/users/{id}").with_validator("id", |id| id != "root"), handler, None);
+    router.post("/users", handler, None);
+    router.any("/static/{*path}", handler, None);
+    router.put(
+        Glob::from("/users/{id}")
+            .with_validator("id", |id| id != "root")
+            .on_validation_failure(ValidationFailure::BadRequest),
+        handler,
+        None
+    );
+
+    let explanation = router.explain(&Method::Get, "/users/bob");
+    assert_eq!(3, explanation.routes.len());
+
+    let posts = &explanation.routes[0];
+    assert!(!posts.matched);
+    assert_eq!(format!("^/users/(?P<id>{})/posts/?$", Type::NUMBER_PATTERN), posts.regex);
+    assert_eq!(Some(MatchFailure {
+        matched_glob: "/users/".to_string(),
+        matched_path: "/users/".to_string(),
+        failed_segment: Some("{id:number}".to_string()),
+        rest: "bob".to_string(),
+    }), posts.failure);
+
+    let user = &explanation.routes[1];
+    assert!(user.matched);
+    assert_eq!(vec![("id".to_string(), "bob".to_string())], user.params);
+    assert_eq!(None, user.failure);
+
+    assert_eq!(concat!(
+        "GET /users/root\n",
+        "  #0 GET /users/{id:number}/posts (posts): not matched\n",
+        "    regex: ^/users/(?P<id>[0-9]+)/posts/?$\n",
+        "    `{id:number}` doesn't match `root` after `/users/`\n",
+        "  #1 GET /users/{id}: rejected by the params\n",
+        "    regex: ^/users/(?P<id>[^/.]+)/?$\n",
+        "    params: id = `root`\n",
+        "  #3 any /static/{*path}: not matched\n",
+        "    regex: ^/static/(?P<path>.*)/?$\n",
+        "    `/static` doesn't match `/users/root` after ``\n",
+    ), router.explain(&Method::Get, "/users/root").to_string());
+
+    let failure = router.explain(&Method::Get, "/users/1/posts/2").routes[0].failure.clone().unwrap();
+    assert_eq!((None, "/2"), (failure.failed_segment, failure.rest.as_str()));
+
+    let explanation = router.explain(&Method::Put, "/users/root");
+    assert!(!explanation.routes[0].matched);
+    assert_eq!(Some("id"), explanation.routes[0].invalid_param.as_ref().map(ParamError::name));
+    assert_eq!(concat!(
+        "  #4 PUT /users/{id}: invalid param `id`, answered with BadRequest\n",
+        "    regex: ^/users/(?P<id>[^/.]+)/?$\n",
+        "    params: id = `root`\n",
+        "    Invalid route param `id` = `root`: rejected by the validator.\n",
+    ), explanation.routes[0].to_string());
+
+    let mut request = Request::new(HyperRequest::new(Method::Get, "http://localhost/missing".parse().unwrap()));
+    let response = router.handle(&mut request).unwrap_err().response.unwrap();
+    assert_eq!(Some(&header::ContentType("application/problem+json".parse().unwrap())), response.headers.get());
+    router.dev_mode(true);
+    let error = router.handle(&mut request).unwrap_err();
+    let response = error.response.unwrap();
+    assert_eq!(StatusCode::NotFound, response.status);
+    assert_eq!(Some(&header::ContentType(mime::TEXT_PLAIN)), response.headers.get());
+}
+
+#[test]
+fn test_suggestions() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.suggestions(true);
+    router.get("/users/{id}", handler, Id::some("user"));
+    router.get("/users/{id}/posts[/{post}]", handler, None);
+    router.post("/usres", handler, None);
+    router.any("/files/{*path}", handler, None);
+
+    let suggested = |method: Method, path: &str| router.suggest(&method, path).iter()
+        .map(|suggestion| (suggestion.route.glob.clone(), suggestion.distance))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![("/users/{id}".to_string(), 2)], suggested(Method::Get, "/usres/1"));
+    assert_eq!(vec![("/users/{id}/posts[/{post}]".to_string(), 1)], suggested(Method::Get, "/users/1/post/2"));
+    assert_eq!(vec![("/files/{*path}".to_string(), 1)], suggested(Method::Delete, "/file/a/b"));
+    assert!(suggested(Method::Get, "/orders").is_empty());
+
+    let not_found = |router: &Router, accept: Option<&str>, path: &str| {
+        let mut request = Request::new(HyperRequest::new(Method::Get, format!("http://localhost{}", path).parse().unwrap()));
+        if let Some(accept) = accept {
+            request.headers.set_raw("Accept", accept.to_string());
+        }
+        let response = router.handle(&mut request).unwrap_err().response.unwrap();
+        assert_eq!(StatusCode::NotFound, response.status);
+        response.headers.get::<header::ContentType>().map(|content_type| content_type.to_string())
+    };
+    let html = Some("text/html; charset=utf-8".to_string());
+    let problem = Some("application/problem+json".to_string());
+    assert_eq!(problem, not_found(&router, None, "/usres/1"));
+    assert_eq!(html, not_found(&router, Some("text/html,application/json;q=0.9"), "/usres/1"));
+
+    router.error_renderer(mime::TEXT_PLAIN, |_: &Request, problem: &Problem| {
+        let suggested = problem.suggestions.iter()
+            .map(|suggestion| suggestion.route.glob.as_str())
+            .collect::<Vec<_>>();
+        match problem.instance.as_str() {
+            "/usres/1" => assert_eq!(vec!["/users/{id}"], suggested),
+            _ => assert!(suggested.is_empty()),
+        }
+        Response::new().with_content(problem.detail.clone(), mime::TEXT_PLAIN)
+    });
+    let text = Some("text/plain".to_string());
+    assert_eq!(text, not_found(&router, Some("text/plain"), "/usres/1"));
+    assert_eq!(text, not_found(&router, Some("text/plain"), "/orders"));
+
+    router.suggestions(false);
+    router.error_renderer(mime::TEXT_PLAIN, |_: &Request, problem: &Problem| {
+        assert!(problem.suggestions.is_empty());
+        Response::new().with_content(problem.detail.clone(), mime::TEXT_PLAIN)
+    });
+    assert_eq!(text, not_found(&router, Some("text/plain"), "/usres/1"));
+}
+
+#[test]
+fn test_fallback() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+    fn fallback(request: &mut Request) -> FerrumResult<Response> {
+        let unmatched = request.extensions.get::<Unmatched>().unwrap().clone();
+        let params = request.extensions.get::<Router>().unwrap();
+        assert!(request.extensions.get::<RouterInner>().is_some());
+        let status = match params.get("path") {
+            Some("teapot") => StatusCode::ImATeapot,
+            Some(_) => StatusCode::Gone,
+            None if unmatched.method == Method::Head => StatusCode::NoContent,
+            None => StatusCode::NotFound,
+        };
+        Ok(Response::new().with_status(status))
+    }
+
+    let mut router = Router::new();
+    router.get("/", handler, None);
+    router.get("/api/users", handler, None);
+
+    let handle = |router: &Router, method: Method, path: &str| {
+        let mut request = Request::new(HyperRequest::new(method, format!("http://localhost{}", path).parse().unwrap()));
+        router.handle(&mut request).map(|response| response.status)
+    };
+    assert!(handle(&router, Method::Get, "/missing").unwrap_err().error.is::<NoRoute>());
+
+    router.fallback_for("/api/{*path}", fallback);
+    assert_eq!(StatusCode::Ok, handle(&router, Method::Get, "/api/users").unwrap());
+    assert_eq!(StatusCode::ImATeapot, handle(&router, Method::Get, "/api/teapot").unwrap());
+    assert_eq!(StatusCode::Gone, handle(&router, Method::Post, "/api/users").unwrap());
+    assert!(handle(&router, Method::Get, "/missing").is_err());
+
+    router.fallback(fallback);
+    assert_eq!(StatusCode::NotFound, handle(&router, Method::Get, "/missing").unwrap());
+    assert_eq!(StatusCode::NoContent, handle(&router, Method::Head, "/missing").unwrap());
+    assert_eq!(StatusCode::Ok, handle(&router, Method::Head, "/").unwrap());
+}
+
+#[test]
+fn test_no_route() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.get("/users/", handler, None);
+    router.post("/users/", handler, None);
+    router.get("/about", handler, None);
+
+    let no_route = |method: Method, path: &str| {
+        let mut request = Request::new(HyperRequest::new(method, format!("http://localhost{}", path).parse().unwrap()));
+        let error = router.handle(&mut request).unwrap_err();
+        assert!(error.error.is::<NoRoute>());
+        error.error.downcast_ref::<NoRoute>().unwrap().clone()
+    };
+
+    let error = no_route(Method::Delete, "/users/");
+    assert_eq!(Method::Delete, error.method);
+    assert_eq!("/users/", error.path);
+    assert_eq!(vec![Method::Get, Method::Post, Method::Head, Method::Options], error.allowed_methods);
+    assert_eq!(None, error.trailing_slash);
+    assert_eq!(None, error.case_variant);
+    assert_eq!("No matching route found for DELETE /users/. Allowed methods: GET, POST, HEAD, OPTIONS.", error.to_string());
+
+    let error = no_route(Method::Get, "/users");
+    assert!(error.allowed_methods.is_empty());
+    assert_eq!(Some("/users/".to_string()), error.trailing_slash);
+    assert_eq!("No matching route found for GET /users. Did you mean `/users/`?", error.to_string());
+
+    let error = no_route(Method::Get, "/About");
+    assert_eq!(None, error.trailing_slash);
+    assert_eq!(Some("/about".to_string()), error.case_variant);
+
+    assert_eq!(NoRoute::new(Method::Get, "/nothing"), no_route(Method::Get, "/nothing"));
+
+    let mut router = Router::new();
+    router.get("/users/", handler, None);
+    router.clear_error_renderers();
+    let mut request = Request::new(HyperRequest::new(Method::Get, "http://localhost/users".parse().unwrap()));
+    let error = router.handle(&mut request).unwrap_err();
+    assert_eq!(Some(&NoRoute::new(Method::Get, "/users")), error.error.downcast_ref::<NoRoute>());
+}
+
+#[test]
+fn test_error_renderers() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+
+    let mut router = Router::new();
+    router.suggestions(false);
+    router.get("/users", handler, None);
+
+    let not_found = |router: &Router, accept: Option<&str>| {
+        let mut request = Request::new(HyperRequest::new(Method::Post, "http://localhost/users".parse().unwrap()));
+        if let Some(accept) = accept {
+            request.headers.set_raw("Accept", accept.to_string());
+        }
+        let response = router.handle(&mut request).unwrap_err().response.unwrap();
+        assert_eq!(StatusCode::NotFound, response.status);
+        assert!(!response.headers.has::<header::Allow>());
+        (response.headers.get::<header::ContentType>().map(|content_type| content_type.to_string()), response.body.is_some())
+    };
+    let problem = (Some("application/problem+json".to_string()), true);
+    let html = (Some("text/html; charset=utf-8".to_string()), true);
+    assert_eq!(problem, not_found(&router, None));
+    assert_eq!(problem, not_found(&router, Some("application/json")));
+    assert_eq!(html, not_found(&router, Some("text/html,application/xhtml+xml,*/*;q=0.8")));
+    assert_eq!(problem, not_found(&router, Some("image/png")));
+
+    router.error_renderer(mime::TEXT_PLAIN, |_: &Request, problem: &Problem| {
+        assert_eq!(vec![Method::Get, Method::Head, Method::Options], problem.allowed_methods);
+        Response::new().with_content(problem.detail.clone(), mime::TEXT_PLAIN)
+    });
+    assert_eq!((Some("text/plain".to_string()), true), not_found(&router, Some("text/plain")));
+    router.error_renderer(mime::TEXT_HTML, ProblemJson);
+    assert_eq!(problem, not_found(&router, Some("text/html")));
+
+    router.clear_error_renderers();
+    assert_eq!((None, false), not_found(&router, Some("text/html")));
+}
+
+#[test]
+fn test_method_not_allowed() {
+    fn handler(_: &mut Request) -> FerrumResult<Response> {
+        Ok(Response::new())
+    }
+    fn pass(_: &mut Request) -> FerrumResult<Response> {
+        Err(Pass.into())
+    }
+
+    let mut router = Router::new();
+    router.method_not_allowed(true);
+    router.get("/users", handler, None);
+    router.get("/drafts", pass, None);
+
+    let handle = |method: Method, path: &str| {
+        let mut request = Request::new(HyperRequest::new(method, format!("http://localhost{}", path).parse().unwrap()));
+        router.handle(&mut request).unwrap_err().response.unwrap()
+    };
+    let response = handle(Method::Post, "/users");
+    assert_eq!(StatusCode::MethodNotAllowed, response.status);
+    assert_eq!(
+        Some(&header::Allow(vec![Method::Get, Method::Head, Method::Options])),
+        response.headers.get::<header::Allow>()
+    );
+    assert_eq!(Some(&header::ContentType("application/problem+json".parse().unwrap())), response.headers.get());
+
+    for response in &[handle(Method::Get, "/drafts"), handle(Method::Get, "/missing")] {
+        assert_eq!(StatusCode::NotFound, response.status);
+        assert!(!response.headers.has::<header::Allow>());
+    }
+}
+
+#[test]
+fn test_pass() {
+    fn article(request: &mut Request) -> FerrumResult<Response> {
+        match request.extensions.get::<Router>().unwrap().get("slug") {
+            Some("known") => Ok(Response::new().with_status(StatusCode::Ok)),
+            _ => Err(Pass.into()),
+        }
+    }
+    fn draft(request: &mut Request) -> FerrumResult<Response> {
+        match request.extensions.get::<Router>().unwrap().get("path") {
+            Some("draft") => Ok(Response::new().with_status(StatusCode::Accepted)),
+            _ => Err(Pass.into()),
+        }
+    }
+    fn any(request: &mut Request) -> FerrumResult<Response> {
+        match request.extensions.get::<MatchedRoute>().unwrap().glob.as_str() {
+            "/articles/{name}" if request.extensions.get::<Router>().unwrap().get("name") != Some("none") => {
+                Ok(Response::new().with_status(StatusCode::ImATeapot))
+            },
+            _ => Err(Pass.into()),
+        }
+    }
+
+    let mut router = Router::new();
+    router.get("/articles/{slug}", article, None);
+    router.get("/articles/{*path}", draft, None);
+    router.any("/articles/{name}", any, None);
+
+    let handle = |method: Method, path: &str| {
+        let mut request = Request::new(HyperRequest::new(method, format!("http://localhost{}", path).parse().unwrap()));
+        router.handle(&mut request).map(|response| response.status)
+    };
+    assert_eq!(StatusCode::Ok, handle(Method::Get, "/articles/known").unwrap());
+    assert_eq!(StatusCode::Accepted, handle(Method::Get, "/articles/draft").unwrap());
+    assert_eq!(StatusCode::ImATeapot, handle(Method::Get, "/articles/other").unwrap());
+    assert_eq!(StatusCode::ImATeapot, handle(Method::Post, "/articles/other").unwrap());
+
+    let error = handle(Method::Get, "/articles/none").unwrap_err();
+    assert!(error.error.is::<NoRoute>());
+    assert_eq!(StatusCode::NotFound, error.response.unwrap().status);
+    assert!(handle(Method::Get, "/articles/none/draft").unwrap_err().error.is::<NoRoute>());
+}
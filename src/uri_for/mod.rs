@@ -2,15 +2,17 @@ use regex::Regex;
 
 use ferrum::{Request, Uri};
 use ferrum::error::{HyperResult, HyperError};
-use router::RouterInner;
-use recognizer::{Recognizer, Params, ParamChunk};
+use request::RouteRequest;
+use recognizer::{Recognizer, Params, GlobAst, GlobNode, for_each_param};
 
 pub trait UriFor {
-    fn generate(&self, glob_path: Option<&str>, recognizer: &Recognizer, params: Params) -> HyperResult<Uri>;
+    /// Generate the URI with the `params` for the `glob` of the `recognizer`, or for its regex
+    /// captures in the path of this URI if there is no glob.
+    fn generate(&self, glob: Option<&GlobAst>, recognizer: &Recognizer, params: Params) -> HyperResult<Uri>;
 }
 
 impl UriFor for Uri {
-    fn generate(&self, glob_path: Option<&str>, recognizer: &Recognizer, mut params: Params) -> HyperResult<Uri> {
+    fn generate(&self, glob: Option<&GlobAst>, recognizer: &Recognizer, mut params: Params) -> HyperResult<Uri> {
         if self != "*" {
             let mut uri = String::new();
 
@@ -23,8 +25,8 @@ impl UriFor for Uri {
                 uri.push_str(authority);
             }
 
-            let path = if let Some(path) = glob_path {
-                generate_for_glob(path, recognizer, &mut params)
+            let path = if let Some(glob) = glob {
+                generate_for_glob(glob, &mut params)
             } else {
                 generate_for_regex_captures(self.path(), &recognizer.glob_regex, &mut params)
             };
@@ -34,14 +36,14 @@ impl UriFor for Uri {
             }
 
             if !params.is_empty() {
-                uri.push_str("?");
+                uri.push('?');
                 let count = params.len();
                 for (index, (ref key, ref value)) in params.into_iter().enumerate() {
                     uri.push_str(key);
-                    uri.push_str("=");
+                    uri.push('=');
                     uri.push_str(value);
                     if index < count - 1 {
-                        uri.push_str("&");
+                        uri.push('&');
                     }
                 }
             }
@@ -53,46 +55,99 @@ impl UriFor for Uri {
     }
 }
 
+/// A value of a param of the `uri_for!` macro, any value convertible into a `String`.
+///
+/// The segments of a `{*name}` tail param can be given as `Segments`, they are percent-encoded
+/// and joined with `/`:
+///
+/// ```ignore
+/// let uri = uri_for!(request, "files", "path" => Segments::from(vec!["docs", "read me.txt"]));
+/// ```
+pub trait IntoUriParam {
+    fn into_uri_param(self) -> String;
+}
+
+impl<T: Into<String>> IntoUriParam for T {
+    fn into_uri_param(self) -> String { self.into() }
+}
+
 /// Generate a URI based off of the currently requested URI.
 ///
 /// The `route_id` used during route registration will be used here again.
 ///
 /// `params` will be inserted as route parameters if fitting, the rest will be appended as query
-/// parameters.
+/// parameters in their order, a query parameter can be repeated with `Params::append`.
+///
+/// Panics if the request wasn't dispatched by a router or there is no route with the id, see
+/// `RouteRequest::uri_for` for the fallible version.
 pub fn uri_for(request: &Request, route_id: &str, params: Params) -> Uri {
-    let inner = request.extensions.get::<RouterInner>()
-        .expect("Couldn\'t find router set up properly.");
-    let (ref glob_path, ref recognizer) = *inner.route_ids.get(route_id)
-        .expect("No route with that ID");
-
-    match request.uri.generate(Some(glob_path), recognizer, params) {
-        Ok(uri) => uri,
-        Err(err) => panic!("New URI parse error: {:?}", err)
-    }
+    RouteRequest::uri_for(request, route_id, params).unwrap_or_else(|error| panic!("{}", error))
 }
 
-pub fn generate_for_glob(source: &str, recognizer: &Recognizer, params: &mut Params) -> String {
-    let mut replacements = vec![];
+/// Generate the path for the glob, the params without values are left as in the glob.
+///
+/// An optional section is generated only if all of its own params have values and any of its
+/// params, including the ones of its nested sections, has a value. A section without params is
+/// always generated. The values of the params of a skipped section are dropped.
+pub fn generate_for_glob(glob: &GlobAst, params: &mut Params) -> String {
+    let mut path = String::new();
+    push_nodes(&glob.nodes, params, &mut path);
+    path
+}
 
-    for &ParamChunk { ref name, start, end } in recognizer.param_chunks.iter() {
-        if let Some(replacement) = params.remove(name) {
-            replacements.push((start, end, replacement));
+fn push_nodes(nodes: &[GlobNode], params: &mut Params, path: &mut String) {
+    for node in nodes.iter() {
+        match *node {
+            GlobNode::Literal(ref text) => path.push_str(text),
+            GlobNode::Param(ref param) => {
+                match param.name.as_ref().and_then(|name| params.remove(name)) {
+                    Some(value) => path.push_str(&value),
+                    None => path.push_str(&param.to_string()),
+                }
+            },
+            GlobNode::Tail(ref tail) => {
+                match params.remove(&tail.name) {
+                    Some(value) => path.push_str(&value),
+                    None => path.push_str(&tail.to_string()),
+                }
+            },
+            GlobNode::Optional(ref nodes) => {
+                // The nested sections don't need to be complete for this one.
+                let own_names = nodes.iter().filter_map(param_name).collect::<Vec<_>>();
+                let mut names = Vec::new();
+                for_each_param(nodes, &mut |node| names.extend(param_name(node)));
+
+                let is_complete = own_names.iter().all(|name| params.contains_key(name));
+                let has_value = names.is_empty() || names.iter().any(|name| params.contains_key(name));
+                if is_complete && has_value {
+                    push_nodes(nodes, params, path);
+                } else {
+                    // They would end up in the query otherwise.
+                    for name in names {
+                        params.remove(name);
+                    }
+                }
+            },
         }
     }
-    replace_params(source, replacements)
 }
 
+fn param_name(node: &GlobNode) -> Option<&String> {
+    match *node {
+        GlobNode::Param(ref param) => param.name.as_ref(),
+        GlobNode::Tail(ref tail) => Some(&tail.name),
+        _ => None,
+    }
+}
 
 pub fn generate_for_regex_captures(source: &str, regex: &Regex, params: &mut Params) -> String {
     let mut replacements = vec![];
 
     if let Some(captures) = regex.captures(source) {
-        for capture_name in regex.capture_names() {
-            if let Some(name) = capture_name {
-                if let Some(replacement) = params.remove(name) {
-                    if let Some(capture_match) = captures.name(name) {
-                        replacements.push((capture_match.start(), capture_match.end(), replacement));
-                    }
+        for name in regex.capture_names().flatten() {
+            if let Some(replacement) = params.remove(name) {
+                if let Some(capture_match) = captures.name(name) {
+                    replacements.push((capture_match.start(), capture_match.end(), replacement));
                 }
             }
         }
@@ -102,7 +157,7 @@ pub fn generate_for_regex_captures(source: &str, regex: &Regex, params: &mut Par
 
 fn replace_params(source: &str, mut replacements: Vec<(usize, usize, String)>) -> String {
     if !replacements.is_empty() {
-        replacements.sort_by(|a, b| a.0.cmp(&b.0));
+        replacements.sort_by_key(|replacement| replacement.0);
 
         let mut target = String::new();
         let mut index = 0;
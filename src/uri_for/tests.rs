@@ -1,6 +1,6 @@
 use super::*;
 use ferrum::Response;
-use recognizer::{Recognizer, DefaultStore};
+use recognizer::{Recognizer, DefaultStore, DefaultStoreBuild};
 
 #[test]
 fn test_uri_generate() {
@@ -66,7 +66,7 @@ fn test_uri_generate() {
         let recognizer = Recognizer::new(pattern, handler, Option::<&DefaultStore>::default()).unwrap();
 
         let uri: Uri = source_uri.parse().unwrap();
-        let uri = uri.generate(Some(pattern), &recognizer, {
+        let uri = uri.generate(Some(&recognizer.glob), &recognizer, {
             let mut params = Params::new();
             for (key, value) in replacements_and_params {
                 params.insert(key.into(), value.into());
@@ -77,6 +77,36 @@ fn test_uri_generate() {
     }
 }
 
+#[test]
+fn test_generate_for_glob() {
+    let types = DefaultStore::with_default_types();
+    let samples = vec![
+        (r"/set/\{{name}\}", vec![("name", "items")], "/set/{items}"),
+        ("/posts/{id:number}.json", vec![("id", "12")], "/posts/12.json"),
+        ("/archive/{year}[/{month}[/{day}]]", vec![("year", "2018")], "/archive/2018"),
+        ("/archive/{year}[/{month}[/{day}]]", vec![("year", "2018"), ("month", "02")], "/archive/2018/02"),
+        ("/archive/{year}[/{month}[/{day}]]", vec![("year", "2018"), ("day", "28")], "/archive/2018"),
+        ("/posts[/{year}/{month}]", vec![("year", "2024")], "/posts"),
+        ("/posts[/{year}/{month}]", vec![("year", "2024"), ("month", "05")], "/posts/2024/05"),
+        ("/docs[/all[/{page}]]", vec![("page", "2")], "/docs/all/2"),
+        ("/docs[/all[/{page}]]", vec![], "/docs"),
+        ("/feed[.xml]", vec![], "/feed.xml"),
+        ("/files/{*path}", vec![("path", "a/b.png")], "/files/a/b.png"),
+        ("/{:[0-9]+}/{ id }", vec![("id", "1")], "/{:[0-9]+}/1"),
+    ];
+
+    for (glob, values, target) in samples {
+        let glob = GlobAst::parse(glob, &types, false).unwrap();
+        let mut params = Params::new();
+        for (key, value) in values {
+            params.insert(key.into(), value.into());
+        }
+
+        assert_eq!(target, generate_for_glob(&glob, &mut params));
+        assert!(params.is_empty());
+    }
+}
+
 #[test]
 fn test_generate_for_regex_captures() {
     let samples = vec![